@@ -0,0 +1,414 @@
+use ringbuf::{
+    traits::{Consumer, Observer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+
+use crate::{dsp::fft::Fft, resampler::Resampler};
+
+/// 分块目标时长（毫秒），实际块长取不小于该值的 2 的幂
+const BLOCK_MS: u32 = 5;
+/// 可建模的回声尾长（毫秒），包含播放/采集缓冲与房间混响
+const TAIL_MS: u32 = 150;
+/// 自适应步长
+const STEP_SIZE: f32 = 0.5;
+/// 远端功率谱的递归平滑系数
+const POWER_SMOOTHING: f32 = 0.9;
+/// 远端块峰值低于该值时不更新滤波器，避免在静音上学习
+const FAR_END_SILENCE: f32 = 1e-3;
+/// Geigel 双讲判决门限：近端峰值超过远端峰值的该比例即判为双讲
+const GEIGEL_THRESHOLD: f32 = 0.5;
+/// 双讲判决后继续冻结自适应的时长（毫秒）
+const DOUBLE_TALK_HOLD_MS: u32 = 80;
+/// 参考缓冲容量（毫秒）
+const REFERENCE_CAPACITY_MS: u32 = 500;
+/// 参考信号允许的最大积压（毫秒），超出后丢弃旧样本以免超出尾长
+const REFERENCE_MAX_BACKLOG_MS: u32 = 40;
+
+#[derive(Clone, Copy, Default)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+/// 分段块频域自适应滤波（重叠保留法）回声消除。
+///
+/// 以环回采集的远端信号为参考估计回声并从麦克风信号中减去，
+/// 每块轮流对一个分段施加时域约束；检测到双讲时冻结自适应。
+/// 引入一个块长的固定时延，缓冲全部在创建时分配。
+pub struct EchoCanceller {
+    fft: Fft,
+    block: usize,
+    bins: usize,
+    /// 分段数 × 频点数的滤波器系数
+    weights: Vec<Complex>,
+    /// 最近各块远端频谱，按环形顺序存放
+    history: Vec<Complex>,
+    newest: usize,
+    partitions: usize,
+    constrain_next: usize,
+    far_power: Vec<f32>,
+    /// 最近各块远端峰值，供 Geigel 判决
+    far_peaks: Vec<f32>,
+    near: Vec<f32>,
+    far: Vec<f32>,
+    /// 上一块与当前块的远端样本
+    far_window: Vec<f32>,
+    output: Vec<f32>,
+    pos: usize,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    error_spectrum: Vec<Complex>,
+    hold_blocks: usize,
+    double_talk_for: usize,
+}
+
+impl EchoCanceller {
+    pub fn new(sample_rate: u32) -> Self {
+        let block = ((sample_rate * BLOCK_MS / 1000) as usize)
+            .next_power_of_two()
+            .max(32);
+        let bins = block + 1;
+        let partitions = (sample_rate as usize * TAIL_MS as usize / 1000).div_ceil(block);
+        let block_ms = block as f32 * 1000.0 / sample_rate.max(1) as f32;
+
+        Self {
+            fft: Fft::new(block * 2),
+            block,
+            bins,
+            weights: vec![Complex::default(); partitions * bins],
+            history: vec![Complex::default(); partitions * bins],
+            newest: 0,
+            partitions,
+            constrain_next: 0,
+            far_power: vec![0.0; bins],
+            far_peaks: vec![0.0; partitions],
+            near: vec![0.0; block],
+            far: vec![0.0; block],
+            far_window: vec![0.0; block * 2],
+            output: vec![0.0; block],
+            pos: 0,
+            re: vec![0.0; block * 2],
+            im: vec![0.0; block * 2],
+            error_spectrum: vec![Complex::default(); bins],
+            hold_blocks: (DOUBLE_TALK_HOLD_MS as f32 / block_ms).ceil() as usize,
+            double_talk_for: 0,
+        }
+    }
+
+    /// 引入的时延（样本数）
+    pub fn latency(&self) -> usize {
+        self.block
+    }
+
+    /// 原地处理麦克风块；`reference` 为同一时段、同一采样率的远端信号
+    pub fn process(&mut self, mic: &mut [f32], reference: &[f32]) {
+        for (sample, &far) in mic.iter_mut().zip(reference) {
+            self.near[self.pos] = *sample;
+            self.far[self.pos] = far;
+            *sample = self.output[self.pos];
+            self.pos += 1;
+            if self.pos == self.block {
+                self.pos = 0;
+                self.process_block();
+            }
+        }
+    }
+
+    fn process_block(&mut self) {
+        let block = self.block;
+        let bins = self.bins;
+
+        // 远端：上一块 + 当前块做 FFT，存入历史环
+        self.far_window.copy_within(block.., 0);
+        self.far_window[block..].copy_from_slice(&self.far);
+        self.re.copy_from_slice(&self.far_window);
+        self.im.fill(0.0);
+        self.fft.forward(&mut self.re, &mut self.im);
+        self.newest = (self.newest + self.partitions - 1) % self.partitions;
+        let newest = self.newest;
+        for k in 0..bins {
+            let x = Complex {
+                re: self.re[k],
+                im: self.im[k],
+            };
+            self.history[newest * bins + k] = x;
+            let power = x.re * x.re + x.im * x.im;
+            self.far_power[k] =
+                POWER_SMOOTHING * self.far_power[k] + (1.0 - POWER_SMOOTHING) * power;
+        }
+        let far_peak = self.far.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        self.far_peaks[newest] = far_peak;
+
+        // 回声估计：各分段系数与对应延迟块频谱相乘后累加
+        self.re.fill(0.0);
+        self.im.fill(0.0);
+        for p in 0..self.partitions {
+            let slot = (newest + p) % self.partitions;
+            let weights = &self.weights[p * bins..(p + 1) * bins];
+            let spectrum = &self.history[slot * bins..(slot + 1) * bins];
+            for k in 0..bins {
+                let (w, x) = (weights[k], spectrum[k]);
+                self.re[k] += w.re * x.re - w.im * x.im;
+                self.im[k] += w.re * x.im + w.im * x.re;
+            }
+        }
+        self.mirror_spectrum();
+        self.fft.inverse(&mut self.re, &mut self.im);
+
+        // 误差 = 近端 - 回声估计（重叠保留取后半段）
+        for i in 0..block {
+            self.output[i] = self.near[i] - self.re[block + i];
+        }
+
+        // Geigel 双讲检测
+        let near_peak = self.near.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        let far_max = self.far_peaks.iter().fold(0.0_f32, |m, &s| m.max(s));
+        if near_peak > GEIGEL_THRESHOLD * far_max {
+            self.double_talk_for = self.hold_blocks;
+        } else {
+            self.double_talk_for = self.double_talk_for.saturating_sub(1);
+        }
+        if far_max < FAR_END_SILENCE || self.double_talk_for > 0 {
+            return;
+        }
+
+        self.re[..block].fill(0.0);
+        self.re[block..].copy_from_slice(&self.output);
+        self.im.fill(0.0);
+        self.fft.forward(&mut self.re, &mut self.im);
+        let floor = self.far_power.iter().sum::<f32>() / bins as f32 * 1e-3 + 1e-10;
+        for k in 0..bins {
+            let norm = STEP_SIZE / (self.far_power[k] * self.partitions as f32 + floor);
+            self.error_spectrum[k] = Complex {
+                re: self.re[k] * norm,
+                im: self.im[k] * norm,
+            };
+        }
+
+        for p in 0..self.partitions {
+            let slot = (newest + p) % self.partitions;
+            for k in 0..bins {
+                // 梯度 = conj(X) · E
+                let x = self.history[slot * bins + k];
+                let e = self.error_spectrum[k];
+                let w = &mut self.weights[p * bins + k];
+                w.re += x.re * e.re + x.im * e.im;
+                w.im += x.re * e.im - x.im * e.re;
+            }
+        }
+        self.constrain(self.constrain_next);
+        self.constrain_next = (self.constrain_next + 1) % self.partitions;
+    }
+
+    /// 将一个分段的系数投影回因果的 `block` 点时域滤波器，抑制循环卷积误差
+    fn constrain(&mut self, partition: usize) {
+        let bins = self.bins;
+        let block = self.block;
+        for k in 0..bins {
+            let w = self.weights[partition * bins + k];
+            self.re[k] = w.re;
+            self.im[k] = w.im;
+        }
+        self.mirror_spectrum();
+        self.fft.inverse(&mut self.re, &mut self.im);
+        self.re[block..].fill(0.0);
+        self.im.fill(0.0);
+        self.fft.forward(&mut self.re, &mut self.im);
+        for k in 0..bins {
+            self.weights[partition * bins + k] = Complex {
+                re: self.re[k],
+                im: self.im[k],
+            };
+        }
+    }
+
+    /// 按前 `bins` 个频点补全共轭对称的另一半
+    fn mirror_spectrum(&mut self) {
+        let n = self.re.len();
+        for k in self.bins..n {
+            self.re[k] = self.re[n - k];
+            self.im[k] = -self.im[n - k];
+        }
+    }
+}
+
+/// 创建参考信号通道：环回回调写入，麦克风回调按输入采样率读出
+pub fn reference_feed(source_rate: u32, target_rate: u32) -> (ReferenceProducer, ReferenceFeed) {
+    let capacity = (source_rate * REFERENCE_CAPACITY_MS / 1000).max(1) as usize;
+    let (producer, consumer) = HeapRb::<f32>::new(capacity).split();
+    (
+        ReferenceProducer { producer },
+        ReferenceFeed {
+            consumer,
+            resampler: Resampler::new(source_rate, target_rate, 1),
+            max_backlog: (source_rate * REFERENCE_MAX_BACKLOG_MS / 1000) as usize,
+            pulled: Vec::with_capacity(source_rate as usize),
+        },
+    )
+}
+
+pub struct ReferenceProducer {
+    producer: HeapProd<f32>,
+}
+
+impl ReferenceProducer {
+    /// 写入单声道远端样本，缓冲已满时丢弃放不下的部分
+    pub fn push(&mut self, samples: &[f32]) {
+        self.producer.push_slice(samples);
+    }
+}
+
+pub struct ReferenceFeed {
+    consumer: HeapCons<f32>,
+    resampler: Resampler,
+    max_backlog: usize,
+    pulled: Vec<f32>,
+}
+
+impl ReferenceFeed {
+    /// 取出与麦克风块等长的参考信号；数据不足时输出静音
+    pub fn pull(&mut self, out: &mut [f32]) {
+        let needed = self.resampler.input_needed(out.len());
+        let available = self.consumer.occupied_len();
+        if available < needed {
+            out.fill(0.0);
+            return;
+        }
+        // 积压过多说明参考相对麦克风越来越滞后，丢弃旧样本重新对齐
+        if available - needed > self.max_backlog {
+            self.consumer.skip(available - needed - self.max_backlog);
+        }
+        self.pulled.resize(needed, 0.0);
+        self.consumer.pop_slice(&mut self.pulled);
+        self.resampler.process(&self.pulled, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn noise(len: usize, amplitude: f32, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    /// 合成回声路径：8ms 纯时延后接 40ms 指数衰减的随机反射，总体约 -17dB
+    fn echo_path() -> Vec<f32> {
+        let delay = RATE as usize * 8 / 1000;
+        let tail = RATE as usize * 40 / 1000;
+        let taps = noise(tail, 1.0, 99);
+        let mut path = vec![0.0; delay + tail];
+        for (i, tap) in taps.iter().enumerate() {
+            path[delay + i] = tap * (-(i as f32) / (tail as f32 / 5.0)).exp() * 0.03;
+        }
+        path
+    }
+
+    fn convolve(signal: &[f32], path: &[f32]) -> Vec<f32> {
+        (0..signal.len())
+            .map(|n| {
+                path.iter()
+                    .enumerate()
+                    .take(n + 1)
+                    .map(|(k, h)| h * signal[n - k])
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// 近端讲话：谐波叠加并按音节调幅
+    fn near_talk(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let voiced: f32 = (1..=8)
+                    .map(|k| (2.0 * std::f32::consts::PI * 180.0 * k as f32 * t).sin() / k as f32)
+                    .sum();
+                voiced * (0.6 + 0.4 * (2.0 * std::f32::consts::PI * 3.0 * t).sin()) * 0.2
+            })
+            .collect()
+    }
+
+    fn run(mic: &[f32], far: &[f32]) -> (Vec<f32>, usize) {
+        let mut aec = EchoCanceller::new(RATE);
+        let mut output = mic.to_vec();
+        for (block, reference) in output.chunks_mut(160).zip(far.chunks(160)) {
+            aec.process(block, reference);
+        }
+        (output, aec.latency())
+    }
+
+    fn power(signal: &[f32]) -> f32 {
+        signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32
+    }
+
+    #[test]
+    fn canceller_should_remove_synthetic_echo() {
+        let len = RATE as usize * 6;
+        let far = noise(len, 0.5, 7);
+        let mic = convolve(&far, &echo_path());
+        let (output, latency) = run(&mic, &far);
+
+        let tail = RATE as usize * 4;
+        let erle = 10.0 * (power(&mic[tail..]) / power(&output[tail + latency..])).log10();
+        assert!(erle > 20.0, "erle = {erle}");
+    }
+
+    #[test]
+    fn double_talk_should_freeze_adaptation_and_keep_near_end() {
+        let len = RATE as usize * 8;
+        let far = noise(len, 0.5, 7);
+        let echo = convolve(&far, &echo_path());
+        // 第 4~5.5 秒近端与远端同时讲话
+        let talk = RATE as usize * 4..RATE as usize * 11 / 2;
+        let mut near = vec![0.0; len];
+        near[talk.clone()].copy_from_slice(&near_talk(talk.len()));
+        let mic: Vec<f32> = echo.iter().zip(&near).map(|(e, n)| e + n).collect();
+        let (output, latency) = run(&mic, &far);
+
+        // 双讲期间近端语音应基本完整保留
+        let during = talk.start + RATE as usize / 4..talk.end;
+        let residual: Vec<f32> = during
+            .clone()
+            .map(|i| output[i + latency] - near[i])
+            .collect();
+        let near_to_residual = 10.0 * (power(&near[during]) / power(&residual)).log10();
+        assert!(
+            near_to_residual > 10.0,
+            "near/residual = {near_to_residual}"
+        );
+
+        // 双讲结束后滤波器未发散，回声仍被消除
+        let after = RATE as usize * 6..len - latency;
+        let erle = 10.0
+            * (power(&mic[after.clone()])
+                / power(&output[after.start + latency..after.end + latency]))
+            .log10();
+        assert!(erle > 15.0, "erle = {erle}");
+    }
+
+    #[test]
+    fn reference_feed_should_resample_and_bound_backlog() {
+        let (mut producer, mut feed) = reference_feed(48_000, 16_000);
+        let mut out = [1.0_f32; 160];
+        // 数据不足时输出静音
+        feed.pull(&mut out);
+        assert!(out.iter().all(|s| *s == 0.0));
+
+        producer.push(&vec![0.25; 48_000 / 5]);
+        feed.pull(&mut out);
+        assert!((out[159] - 0.25).abs() < 1e-3);
+        // 超出允许积压的旧样本被丢弃
+        assert!(feed.consumer.occupied_len() <= feed.max_backlog);
+    }
+}
@@ -1,69 +1,128 @@
 use std::{
-    sync::{mpsc, Arc},
-    thread,
-    time::Duration,
+    sync::{mpsc, Arc, OnceLock},
+    thread::{self, Thread},
+    time::{Duration, Instant},
 };
 
 use parking_lot::Mutex;
 
 use crate::{
     audio::{complete_route_defaults, list_devices, EngineRuntime},
-    config, driver_installer,
+    backoff::Backoff,
+    config, device_identity, driver_installer, dsp,
     error::AppError,
+    failover,
     gate::GateController,
     hotkey::HotkeyManager,
-    types::{AppConfig, AudioRouteConfig, EngineState, GateState, RuntimeStatus, VirtualMicStatus},
+    meter::LevelMonitor,
+    types::{
+        AppConfig, AudioLevels, AudioRouteConfig, DeviceList, DspChainConfig, EngineConfig,
+        EngineState, GateState, MutedSpeechWarning, RouteChange, RuntimeStatus, VirtualMicStatus,
+        VoiceShapingConfig,
+    },
     virtual_mic,
 };
 
+/// 引擎异常后第一次重试之后的等待时间，此后逐次翻倍
+const RESTART_BACKOFF_INITIAL: Duration = Duration::from_secs(2);
+/// 重启等待时间上限
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+/// 引擎连续运行超过该时长视为已恢复稳定，退避回到第一档
+const RESTART_STABLE_AFTER: Duration = Duration::from_secs(30);
+/// 等待工作线程完成输入设备切换的最长时间
+const INPUT_SWITCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 某个输入设备保存的音色设置，未保存过时使用默认值
+fn input_shaping(cfg: &AppConfig, device_id: &str) -> VoiceShapingConfig {
+    cfg.input_profiles
+        .get(device_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// 按指纹重新绑定配置中失效的设备 id，并记录日志
+fn rebind_devices(cfg: &mut AppConfig, devices: &DeviceList) {
+    for rebind in device_identity::rebind_config(cfg, devices) {
+        log::info!(
+            "设备 id 已重新匹配: {} -> {}（置信度 {:.2}）",
+            rebind.old_id,
+            rebind.new_id,
+            rebind.confidence
+        );
+    }
+}
+
+enum WorkerCommand {
+    Stop,
+    UpdateDsp(DspChainConfig),
+    UpdateShaping(VoiceShapingConfig),
+    SwitchInput {
+        route: AudioRouteConfig,
+        shaping: VoiceShapingConfig,
+        reply: mpsc::Sender<Result<(), AppError>>,
+    },
+}
+
 struct EngineWorker {
-    stop_tx: mpsc::Sender<()>,
+    /// 实际使用的路由，首选设备不可用时为备用设备
+    route: AudioRouteConfig,
+    started_at: Instant,
+    levels: Arc<LevelMonitor>,
+    commands: mpsc::Sender<WorkerCommand>,
     join_handle: Option<thread::JoinHandle<()>>,
     snapshot: Arc<Mutex<RuntimeStatus>>,
 }
 
 impl EngineWorker {
     fn new(
-        input_device_id: String,
-        output_device_id: String,
+        route: AudioRouteConfig,
+        engine: EngineConfig,
+        dsp: DspChainConfig,
+        shaping: VoiceShapingConfig,
         gate: Arc<GateController>,
     ) -> Result<Self, AppError> {
-        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let (commands, command_rx) = mpsc::channel::<WorkerCommand>();
         let (started_tx, started_rx) = mpsc::sync_channel::<Result<(), String>>(1);
         let snapshot = Arc::new(Mutex::new(RuntimeStatus {
-            engine_state: EngineState::Idle,
-            buffer_level_ms: 0,
-            xruns: 0,
-            last_error: None,
             gate_state: gate.snapshot(),
+            ..RuntimeStatus::default()
         }));
 
         let snapshot_for_thread = snapshot.clone();
+        let levels = Arc::new(LevelMonitor::default());
+        let worker_levels = levels.clone();
+        let active_route = route.clone();
         let join_handle = thread::spawn(move || {
-            let runtime =
-                match EngineRuntime::start(&input_device_id, &output_device_id, gate.clone()) {
-                    Ok(runtime) => {
-                        {
-                            let mut status = snapshot_for_thread.lock();
-                            status.engine_state = EngineState::Running;
-                            status.last_error = None;
-                            status.gate_state = gate.snapshot();
-                        }
-                        let _ = started_tx.send(Ok(()));
-                        runtime
+            let mut runtime = match EngineRuntime::start(
+                &route,
+                &engine,
+                &dsp,
+                &shaping,
+                gate.clone(),
+                worker_levels,
+            ) {
+                Ok(runtime) => {
+                    {
+                        let mut status = snapshot_for_thread.lock();
+                        status.engine_state = EngineState::Running;
+                        status.last_error = None;
+                        status.gate_state = gate.snapshot();
                     }
-                    Err(e) => {
-                        let msg = e.to_string();
-                        {
-                            let mut status = snapshot_for_thread.lock();
-                            status.engine_state = EngineState::Error;
-                            status.last_error = Some(msg.clone());
-                            status.gate_state = gate.snapshot();
-                        }
-                        let _ = started_tx.send(Err(msg));
-                        return;
+                    let _ = started_tx.send(Ok(()));
+                    runtime
+                }
+                Err(e) => {
+                    let msg = e.to_string();
+                    {
+                        let mut status = snapshot_for_thread.lock();
+                        status.engine_state = EngineState::Error;
+                        status.last_error = Some(msg.clone());
+                        status.gate_state = gate.snapshot();
                     }
-                };
+                    let _ = started_tx.send(Err(msg));
+                    return;
+                }
+            };
 
             loop {
                 {
@@ -71,8 +130,28 @@ impl EngineWorker {
                     *status = runtime.status(gate.snapshot());
                 }
 
-                match stop_rx.recv_timeout(Duration::from_millis(150)) {
-                    Ok(_) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                match command_rx.recv_timeout(Duration::from_millis(150)) {
+                    Ok(WorkerCommand::UpdateDsp(config)) => {
+                        if let Err(e) = runtime.update_dsp(&config) {
+                            log::warn!("更新处理链失败: {e}");
+                        }
+                    }
+                    Ok(WorkerCommand::UpdateShaping(config)) => {
+                        if let Err(e) = runtime.update_shaping(&config) {
+                            log::warn!("更新音色设置失败: {e}");
+                        }
+                    }
+                    Ok(WorkerCommand::SwitchInput {
+                        route,
+                        shaping,
+                        reply,
+                    }) => {
+                        let result = runtime.switch_input(&route, &shaping);
+                        // 先刷新快照再回复，调用方据此确认实际使用的输入设备
+                        *snapshot_for_thread.lock() = runtime.status(gate.snapshot());
+                        let _ = reply.send(result);
+                    }
+                    Ok(WorkerCommand::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => {
                         break;
                     }
                     Err(mpsc::RecvTimeoutError::Timeout) => {}
@@ -85,7 +164,10 @@ impl EngineWorker {
 
         match started_rx.recv_timeout(Duration::from_secs(5)) {
             Ok(Ok(())) => Ok(Self {
-                stop_tx,
+                route: active_route,
+                started_at: Instant::now(),
+                levels,
+                commands,
                 join_handle: Some(join_handle),
                 snapshot,
             }),
@@ -94,15 +176,30 @@ impl EngineWorker {
                 Err(AppError::Audio(msg))
             }
             Err(_) => {
-                let _ = stop_tx.send(());
+                let _ = commands.send(WorkerCommand::Stop);
                 let _ = join_handle.join();
                 Err(AppError::Audio("启动音频引擎超时".to_string()))
             }
         }
     }
 
+    fn update_dsp(&self, config: DspChainConfig) {
+        let _ = self.commands.send(WorkerCommand::UpdateDsp(config));
+    }
+
+    fn update_shaping(&self, config: VoiceShapingConfig) {
+        let _ = self.commands.send(WorkerCommand::UpdateShaping(config));
+    }
+
+    /// 按运行时报告的输入设备更新实际路由，未切换成功时保持不变
+    fn sync_input(&mut self, requested: &AudioRouteConfig) {
+        if self.snapshot.lock().active_input_device_id == requested.input_device_id {
+            self.route = requested.clone();
+        }
+    }
+
     fn stop(mut self) {
-        let _ = self.stop_tx.send(());
+        let _ = self.commands.send(WorkerCommand::Stop);
         if let Some(handle) = self.join_handle.take() {
             let _ = handle.join();
         }
@@ -113,6 +210,24 @@ impl EngineWorker {
         status.gate_state = gate_state;
         status
     }
+
+    /// 音频流出错后返回错误信息
+    fn fault(&self) -> Option<String> {
+        let status = self.snapshot.lock();
+        (status.engine_state == EngineState::Error)
+            .then(|| status.last_error.clone().unwrap_or_default())
+    }
+}
+
+/// 引擎异常退出后的自动恢复状态
+struct Recovery {
+    /// 用户启动过引擎且未主动停止，异常后应自动重启
+    wanted: bool,
+    backoff: Backoff,
+    /// 下一次重启尝试的时间，为 None 时无需重启
+    retry_at: Option<Instant>,
+    /// 停止前实际使用的路由，恢复后据此生成 `route_changed` 事件
+    last_route: Option<AudioRouteConfig>,
 }
 
 pub struct AppState {
@@ -120,8 +235,11 @@ pub struct AppState {
     pub hotkey: HotkeyManager,
     config: Mutex<AppConfig>,
     engine: Mutex<Option<EngineWorker>>,
+    recovery: Mutex<Recovery>,
     last_error: Mutex<Option<String>>,
     virtual_mic_status: Mutex<VirtualMicStatus>,
+    /// 推送电平的线程，引擎未运行时挂起，启动引擎后唤醒
+    level_listener: OnceLock<Thread>,
 }
 
 impl AppState {
@@ -141,8 +259,15 @@ impl AppState {
             hotkey: HotkeyManager::default(),
             config: Mutex::new(cfg),
             engine: Mutex::new(None),
+            recovery: Mutex::new(Recovery {
+                wanted: false,
+                backoff: Backoff::new(RESTART_BACKOFF_INITIAL, RESTART_BACKOFF_MAX),
+                retry_at: None,
+                last_route: None,
+            }),
             last_error: Mutex::new(None),
             virtual_mic_status: Mutex::new(vm_status),
+            level_listener: OnceLock::new(),
         };
 
         if let Err(e) = state.ensure_route_defaults() {
@@ -156,26 +281,119 @@ impl AppState {
         self.config.lock().clone()
     }
 
+    /// 保存路由；引擎运行中时立即生效：只改了输入侧设置时热切换输入流，输出流保持运行，
+    /// 其余变化重启引擎
     pub fn set_route(&self, mut route: AudioRouteConfig) -> Result<(), AppError> {
-        let mut cfg = self.config.lock();
+        // 完整枚举较慢，先于加锁进行，避免阻塞其它命令
+        let devices = list_devices()?;
+        let mut current = self.config.lock();
 
         if route.bridge_output_device_id.is_empty() {
-            route.bridge_output_device_id = cfg.route.bridge_output_device_id.clone();
+            route.bridge_output_device_id = current.route.bridge_output_device_id.clone();
+        }
+
+        if !route.reference_output_device_id.is_empty()
+            && route.reference_output_device_id == route.bridge_output_device_id
+        {
+            return Err(AppError::InvalidArgument(
+                "回声参考设备不能与桥接输出设备相同".to_string(),
+            ));
         }
 
+        // 在副本上校正与补全，保存成功后才替换内存中的配置，失败时保持与磁盘一致
+        let mut cfg = current.clone();
         cfg.route = route;
-        complete_route_defaults(&mut cfg.route)?;
-        config::save_config(&cfg)
+        rebind_devices(&mut cfg, &devices);
+        complete_route_defaults(&mut cfg.route, &devices)?;
+        config::save_config(&cfg)?;
+        *current = cfg.clone();
+        drop(current);
+
+        self.apply_route(&cfg, &devices)
+    }
+
+    /// 让运行中的引擎切换到新保存的路由
+    fn apply_route(&self, cfg: &AppConfig, devices: &DeviceList) -> Result<(), AppError> {
+        let Some(active) = self
+            .engine
+            .lock()
+            .as_ref()
+            .map(|worker| worker.route.clone())
+        else {
+            return Ok(());
+        };
+        // 解析失败时交给重启流程报告错误并按退避重试
+        if let Ok(desired) = failover::resolve_active_route(&cfg.route, devices) {
+            if failover::same_output_side(&active, &desired) {
+                if desired.input_device_id == active.input_device_id
+                    && desired.input_channel == active.input_channel
+                {
+                    if let Some(worker) = self.engine.lock().as_mut() {
+                        worker.route = desired;
+                    }
+                    return Ok(());
+                }
+                let shaping = input_shaping(cfg, &desired.input_device_id);
+                match self.switch_input(&desired, shaping) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => log::warn!("无缝切换输入设备失败，改为重启引擎: {e}"),
+                }
+            }
+        }
+
+        self.stop_engine();
+        self.start_engine()
+    }
+
+    /// 不停止输出流，把运行中引擎的输入换到 `desired`。
+    ///
+    /// 等待工作线程期间不持有引擎锁，以免阻塞电平推送、状态查询与停止引擎。
+    fn switch_input(
+        &self,
+        desired: &AudioRouteConfig,
+        shaping: VoiceShapingConfig,
+    ) -> Result<(), AppError> {
+        let (commands, started_at) = {
+            let engine = self.engine.lock();
+            let worker = engine
+                .as_ref()
+                .ok_or_else(|| AppError::Audio("音频引擎未运行".to_string()))?;
+            (worker.commands.clone(), worker.started_at)
+        };
+        let (reply, result) = mpsc::channel();
+        let _ = commands.send(WorkerCommand::SwitchInput {
+            route: desired.clone(),
+            shaping,
+            reply,
+        });
+        let result = result
+            .recv_timeout(INPUT_SWITCH_TIMEOUT)
+            .map_err(|_| AppError::Audio("切换输入设备超时".to_string()))
+            .and_then(|result| result);
+
+        // 等待期间引擎可能已被停止或重启，只更新同一个工作线程
+        if let Some(worker) = self
+            .engine
+            .lock()
+            .as_mut()
+            .filter(|worker| worker.started_at == started_at)
+        {
+            worker.sync_input(desired);
+        }
+        result
     }
 
-    pub fn ensure_route_defaults(&self) -> Result<(), AppError> {
+    /// 按设备指纹校正路由中的设备 id（含旧版 id 迁移），并补全缺省设备；返回本次枚举的设备
+    pub fn ensure_route_defaults(&self) -> Result<DeviceList, AppError> {
         let mut cfg = self.config.lock();
-        let before = cfg.route.clone();
-        complete_route_defaults(&mut cfg.route)?;
-        if cfg.route != before {
+        let before = (cfg.route.clone(), cfg.device_fingerprints.clone());
+        let devices = list_devices()?;
+        rebind_devices(&mut cfg, &devices);
+        complete_route_defaults(&mut cfg.route, &devices)?;
+        if (cfg.route.clone(), cfg.device_fingerprints.clone()) != before {
             config::save_config(&cfg)?;
         }
-        Ok(())
+        Ok(devices)
     }
 
     pub fn set_hotkey_config(&self, hotkey: crate::types::HotkeyConfig) -> Result<(), AppError> {
@@ -185,6 +403,72 @@ impl AppState {
         config::save_config(&cfg)
     }
 
+    /// 保存引擎参数；引擎运行中时重启使其生效
+    pub fn set_engine_config(&self, engine: EngineConfig) -> Result<(), AppError> {
+        {
+            let mut cfg = self.config.lock();
+            if cfg.engine == engine {
+                return Ok(());
+            }
+            cfg.engine = engine;
+            config::save_config(&cfg)?;
+        }
+
+        if self.engine.lock().is_some() {
+            self.stop_engine();
+            self.start_engine()?;
+        }
+        Ok(())
+    }
+
+    /// 保存处理链；引擎运行中时无需重启，直接投递到音频线程
+    pub fn set_dsp_chain(&self, chain: DspChainConfig) -> Result<(), AppError> {
+        dsp::validate_chain(&chain)?;
+        {
+            let mut cfg = self.config.lock();
+            if cfg.dsp == chain {
+                return Ok(());
+            }
+            cfg.dsp = chain.clone();
+            config::save_config(&cfg)?;
+        }
+
+        if let Some(engine) = self.engine.lock().as_ref() {
+            engine.update_dsp(chain);
+        }
+        Ok(())
+    }
+
+    /// 保存某个输入设备的音色设置；该设备正在使用时直接投递到音频线程
+    pub fn set_input_profile(
+        &self,
+        device_id: String,
+        profile: VoiceShapingConfig,
+    ) -> Result<(), AppError> {
+        dsp::validate_shaping(&profile)?;
+        if device_id.is_empty() {
+            return Err(AppError::InvalidArgument(
+                "输入设备 id 不能为空".to_string(),
+            ));
+        }
+        {
+            let mut cfg = self.config.lock();
+            if cfg.input_profiles.get(&device_id) == Some(&profile) {
+                return Ok(());
+            }
+            cfg.input_profiles
+                .insert(device_id.clone(), profile.clone());
+            config::save_config(&cfg)?;
+        }
+
+        if let Some(engine) = self.engine.lock().as_ref() {
+            if engine.route.input_device_id == device_id {
+                engine.update_shaping(profile);
+            }
+        }
+        Ok(())
+    }
+
     pub fn set_launch_on_startup(&self, enabled: bool) -> Result<(), AppError> {
         let mut cfg = self.config.lock();
         cfg.launch_on_startup = enabled;
@@ -197,14 +481,29 @@ impl AppState {
         config::save_config(&cfg)
     }
 
+    /// 启动引擎；失败或之后出现异常时会按退避时间自动重试，直到调用 `stop_engine`
     pub fn start_engine(&self) -> Result<(), AppError> {
+        let mut recovery = self.recovery.lock();
+        recovery.wanted = true;
+        recovery.last_route = None;
+        let result = self.start_worker();
+        if result.is_ok() {
+            recovery.retry_at = None;
+            recovery.backoff.reset();
+        } else {
+            recovery.retry_at = Some(Instant::now() + recovery.backoff.next_delay());
+        }
+        result
+    }
+
+    fn start_worker(&self) -> Result<(), AppError> {
         if self.engine.lock().is_some() {
             return Ok(());
         }
 
         let result = (|| -> Result<EngineWorker, AppError> {
-            let mut cfg = self.config.lock().clone();
-            complete_route_defaults(&mut cfg.route)?;
+            let devices = self.ensure_route_defaults()?;
+            let cfg = self.config.lock().clone();
 
             if cfg.route.input_device_id.is_empty() || cfg.route.bridge_output_device_id.is_empty()
             {
@@ -213,9 +512,22 @@ impl AppState {
                 ));
             }
 
+            let route = failover::resolve_active_route(&cfg.route, &devices)?;
+            if route.input_device_id != cfg.route.input_device_id
+                || route.bridge_output_device_id != cfg.route.bridge_output_device_id
+            {
+                log::info!(
+                    "首选设备不可用，使用备用设备启动: 输入 {} 输出 {}",
+                    route.input_device_id,
+                    route.bridge_output_device_id
+                );
+            }
+
             EngineWorker::new(
-                cfg.route.input_device_id.clone(),
-                cfg.route.bridge_output_device_id.clone(),
+                route.clone(),
+                cfg.engine.clone(),
+                cfg.dsp.clone(),
+                input_shaping(&cfg, &route.input_device_id),
                 self.gate.clone(),
             )
         })();
@@ -224,6 +536,9 @@ impl AppState {
             Ok(worker) => {
                 *self.engine.lock() = Some(worker);
                 *self.last_error.lock() = None;
+                if let Some(listener) = self.level_listener.get() {
+                    listener.unpark();
+                }
                 Ok(())
             }
             Err(e) => {
@@ -234,10 +549,133 @@ impl AppState {
     }
 
     pub fn stop_engine(&self) {
+        let mut recovery = self.recovery.lock();
+        recovery.wanted = false;
+        recovery.retry_at = None;
+        recovery.last_route = None;
         if let Some(worker) = self.engine.lock().take() {
             worker.stop();
         }
     }
+
+    /// 故障转移线程定期调用，`devices` 为最近一次完整枚举的结果：
+    /// - 音频流出错或正在使用的设备断开时停止引擎，按指数退避重启；
+    /// - 优先级更高的设备重新出现、或跟随的系统默认输入变化时立即切换；
+    /// - 设备有增减时跳过剩余的退避等待，立即尝试恢复。
+    ///
+    /// 返回恢复或切换后实际使用设备的变化。
+    pub fn supervise(
+        &self,
+        devices: &DeviceList,
+        devices_changed: bool,
+    ) -> Result<Vec<RouteChange>, AppError> {
+        let now = Instant::now();
+        // 在配置副本上校正 id，仅用于比较；真正的校正与保存在启动引擎时进行
+        let mut cfg = self.config();
+        device_identity::rebind_config(&mut cfg, devices);
+
+        let running = self
+            .engine
+            .lock()
+            .as_ref()
+            .map(|engine| (engine.route.clone(), engine.fault(), engine.started_at));
+        if let Some((active, fault, started_at)) = running {
+            let present = devices
+                .inputs
+                .iter()
+                .any(|d| d.id == active.input_device_id)
+                && devices
+                    .outputs
+                    .iter()
+                    .any(|d| d.id == active.bridge_output_device_id);
+            let failure = match fault {
+                Some(e) => Some(format!("音频流异常: {e}")),
+                None if !present => Some("正在使用的音频设备已断开".to_string()),
+                None => None,
+            };
+
+            if failure.is_none() {
+                let desired = failover::resolve_active_route(&cfg.route, devices)
+                    .ok()
+                    .filter(|desired| {
+                        desired.input_device_id != active.input_device_id
+                            || desired.bridge_output_device_id != active.bridge_output_device_id
+                    });
+                let Some(desired) = desired else {
+                    if started_at.elapsed() >= RESTART_STABLE_AFTER {
+                        self.recovery.lock().backoff.reset();
+                    }
+                    return Ok(Vec::new());
+                };
+
+                // 只有输入设备变化（跟随的默认设备变化、切回首选输入）时只换输入流，与原设备交叉淡化
+                if failover::same_output_side(&active, &desired) {
+                    let shaping = input_shaping(&cfg, &desired.input_device_id);
+                    match self.switch_input(&desired, shaping) {
+                        Ok(()) => {
+                            return Ok(failover::route_changes(
+                                &cfg.route, &active, &desired, devices,
+                            ));
+                        }
+                        Err(e) => log::warn!("无缝切换输入设备失败，改为重启引擎: {e}"),
+                    }
+                }
+            }
+
+            // 检查期间用户可能已停止引擎，此时不再重新启动
+            let Some(worker) = self.engine.lock().take() else {
+                return Ok(Vec::new());
+            };
+            worker.stop();
+
+            let mut recovery = self.recovery.lock();
+            recovery.last_route = Some(active);
+            // 切回首选设备立即进行；异常按退避时间重试
+            let delay = match failure {
+                Some(msg) => {
+                    log::warn!("{msg}，准备重启音频引擎");
+                    *self.last_error.lock() = Some(msg);
+                    recovery.backoff.next_delay()
+                }
+                None => Duration::ZERO,
+            };
+            recovery.retry_at = Some(now + delay);
+        }
+
+        // 持锁启动，避免与用户的停止操作交错
+        let mut recovery = self.recovery.lock();
+        let due = match recovery.retry_at {
+            Some(at) => now >= at || devices_changed,
+            None => false,
+        };
+        if !recovery.wanted || !due {
+            return Ok(Vec::new());
+        }
+
+        if let Err(e) = self.start_worker() {
+            let delay = recovery.backoff.next_delay();
+            recovery.retry_at = Some(Instant::now() + delay);
+            return Err(AppError::Audio(format!(
+                "重启音频引擎失败，{} 秒后重试: {e}",
+                delay.as_secs()
+            )));
+        }
+        recovery.retry_at = None;
+        let previous = recovery.last_route.take();
+        let next = self
+            .engine
+            .lock()
+            .as_ref()
+            .map(|engine| engine.route.clone());
+        Ok(match (previous, next) {
+            (Some(previous), Some(next)) => {
+                log::info!("音频引擎已恢复");
+                failover::route_changes(&cfg.route, &previous, &next, devices)
+            }
+            _ => Vec::new(),
+        })
+    }
+
     pub fn gate_snapshot(&self) -> GateState {
         self.gate.snapshot()
     }
@@ -292,13 +730,41 @@ impl AppState {
             } else {
                 EngineState::Idle
             },
-            buffer_level_ms: 0,
-            xruns: 0,
             last_error: self.last_error.lock().clone(),
             gate_state,
+            ..RuntimeStatus::default()
         }
     }
 
+    /// 注册推送电平的线程，引擎启动时唤醒它
+    pub fn set_level_listener(&self, listener: Thread) {
+        let _ = self.level_listener.set(listener);
+    }
+
+    /// 引擎运行中时返回最新电平
+    pub fn audio_levels(&self) -> Option<AudioLevels> {
+        self.engine
+            .lock()
+            .as_ref()
+            .map(|engine| engine.levels.snapshot())
+    }
+
+    /// 取走音频线程标记的闭麦说话提醒
+    pub fn take_muted_speech(&self) -> Option<MutedSpeechWarning> {
+        let level = {
+            let engine = self.engine.lock();
+            let levels = &engine.as_ref()?.levels;
+            if !levels.take_muted_speech() {
+                return None;
+            }
+            levels.pre_gate.load()
+        };
+        Some(MutedSpeechWarning {
+            level,
+            gate_state: self.gate.snapshot(),
+        })
+    }
+
     pub fn validate_route_exists(&self) -> Result<(), AppError> {
         let cfg = self.config.lock().clone();
         if cfg.route.input_device_id.is_empty() && cfg.route.bridge_output_device_id.is_empty() {
@@ -306,18 +772,9 @@ impl AppState {
         }
 
         let devices = list_devices()?;
-        let in_ok = devices
-            .inputs
-            .iter()
-            .any(|d| d.id == cfg.route.input_device_id);
-        let out_ok = devices
-            .outputs
-            .iter()
-            .any(|d| d.id == cfg.route.bridge_output_device_id);
-
-        if !in_ok || !out_ok {
+        if failover::resolve_active_route(&cfg.route, &devices).is_err() {
             return Err(AppError::DeviceNotFound(
-                "历史配置中的音频设备已不可用，请重新选择输入设备".to_string(),
+                "历史配置中的音频设备及备用设备均已不可用，请重新选择输入设备".to_string(),
             ));
         }
 
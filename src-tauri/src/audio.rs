@@ -1,8 +1,13 @@
 use cpal::traits::{DeviceTrait, HostTrait};
 
 use crate::{
+    device_identity::assign_ids,
+    device_watcher::DeviceSummary,
     error::AppError,
-    types::{AudioRouteConfig, DeviceDirection, DeviceInfo, DeviceList},
+    types::{
+        AudioRouteConfig, ConfigSignature, DeviceDirection, DeviceFingerprint, DeviceInfo,
+        DeviceList,
+    },
 };
 
 fn host() -> cpal::Host {
@@ -16,12 +21,146 @@ fn is_virtual_candidate(name: &str) -> bool {
         .any(|k| lower.contains(k))
 }
 
-fn make_device_id(direction: &DeviceDirection, index: usize, name: &str) -> String {
-    let d = match direction {
-        DeviceDirection::Input => "in",
-        DeviceDirection::Output => "out",
+/// 按 cpal 的枚举顺序读取某一方向活动端点的 (友好名称, WASAPI 端点 ID)。
+///
+/// cpal 未暴露底层的 `IMMDevice`，这里按它的方式（全部活动端点再按数据流向过滤）重新枚举一次，
+/// 调用方按名称核对后才采用；读取失败时返回空列表，指纹退回名称与格式匹配。
+#[cfg(target_os = "windows")]
+fn endpoint_ids(direction: DeviceDirection) -> Vec<(String, Option<String>)> {
+    use windows::{
+        core::Interface,
+        Win32::{
+            Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+            Media::Audio::{
+                eAll, eCapture, eRender, IMMDeviceEnumerator, IMMEndpoint, MMDeviceEnumerator,
+                DEVICE_STATE_ACTIVE,
+            },
+            System::Com::{
+                CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL,
+                COINIT_APARTMENTTHREADED, STGM_READ,
+            },
+        },
+    };
+
+    let flow = match direction {
+        DeviceDirection::Input => eCapture,
+        DeviceDirection::Output => eRender,
+    };
+    unsafe {
+        // 与 cpal 一样按单线程套间初始化；本线程已按其它模式初始化时 COM 照常可用，但不能反初始化
+        let com = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let read = || -> windows::core::Result<Vec<(String, Option<String>)>> {
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let collection = enumerator.EnumAudioEndpoints(eAll, DEVICE_STATE_ACTIVE)?;
+            let mut endpoints = Vec::new();
+            for i in 0..collection.GetCount()? {
+                let device = collection.Item(i)?;
+                if device.cast::<IMMEndpoint>()?.GetDataFlow()? != flow {
+                    continue;
+                }
+                let raw = device.GetId()?;
+                let id = raw.to_string().ok();
+                CoTaskMemFree(Some(raw.0 as *const _));
+                let name = device
+                    .OpenPropertyStore(STGM_READ)?
+                    .GetValue(&PKEY_Device_FriendlyName)?
+                    .to_string();
+                endpoints.push((name, id));
+            }
+            Ok(endpoints)
+        };
+        // COM 对象在闭包返回时已释放，之后才能反初始化
+        let endpoints = read();
+        if com.is_ok() {
+            CoUninitialize();
+        }
+        endpoints.unwrap_or_else(|e| {
+            log::warn!("读取音频端点 ID 失败: {e}");
+            Vec::new()
+        })
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn endpoint_ids(_direction: DeviceDirection) -> Vec<(String, Option<String>)> {
+    Vec::new()
+}
+
+/// 读取设备指纹；读取支持格式失败时格式列表为空。
+fn fingerprint(
+    device: &cpal::Device,
+    direction: DeviceDirection,
+    host: &str,
+    endpoint_id: Option<String>,
+) -> DeviceFingerprint {
+    let name = device.name().unwrap_or_else(|_| match direction {
+        DeviceDirection::Input => "未知输入设备".to_string(),
+        DeviceDirection::Output => "未知输出设备".to_string(),
+    });
+    let signature = |cfg: cpal::SupportedStreamConfigRange| ConfigSignature {
+        channels: cfg.channels(),
+        min_sample_rate: cfg.min_sample_rate().0,
+        max_sample_rate: cfg.max_sample_rate().0,
+        sample_format: cfg.sample_format().to_string(),
     };
-    format!("{}#{}#{}", d, index, name)
+    let mut configs: Vec<ConfigSignature> = match direction {
+        DeviceDirection::Input => device
+            .supported_input_configs()
+            .map(|configs| configs.map(signature).collect())
+            .unwrap_or_default(),
+        DeviceDirection::Output => device
+            .supported_output_configs()
+            .map(|configs| configs.map(signature).collect())
+            .unwrap_or_default(),
+    };
+    configs.sort();
+    configs.dedup();
+    DeviceFingerprint {
+        direction,
+        name,
+        host: host.to_string(),
+        endpoint_id,
+        configs,
+    }
+}
+
+/// 枚举某一方向的设备，返回 (稳定 id, 设备, 指纹)
+fn enumerate(
+    host: &cpal::Host,
+    direction: DeviceDirection,
+) -> Result<Vec<(String, cpal::Device, DeviceFingerprint)>, AppError> {
+    let devices: Vec<cpal::Device> = match direction {
+        DeviceDirection::Input => host
+            .input_devices()
+            .map_err(|e| AppError::Audio(format!("获取输入设备失败: {e}")))?
+            .collect(),
+        DeviceDirection::Output => host
+            .output_devices()
+            .map_err(|e| AppError::Audio(format!("获取输出设备失败: {e}")))?
+            .collect(),
+    };
+    let host_name = host.id().name();
+    // 同名设备按出现顺序对应，两次枚举之间设备变化导致对不上的项不带端点 ID
+    let mut endpoints = endpoint_ids(direction);
+    let fingerprints: Vec<DeviceFingerprint> = devices
+        .iter()
+        .map(|device| {
+            let name = device.name().unwrap_or_default();
+            let endpoint_id = endpoints
+                .iter()
+                .position(|(endpoint, _)| *endpoint == name)
+                .and_then(|i| endpoints.remove(i).1);
+            fingerprint(device, direction, host_name, endpoint_id)
+        })
+        .collect();
+    let ids = assign_ids(&fingerprints);
+    Ok(ids
+        .into_iter()
+        .zip(devices)
+        .zip(fingerprints)
+        .map(|((id, device), fingerprint)| (id, device, fingerprint))
+        .collect())
 }
 
 pub fn list_devices() -> Result<DeviceList, AppError> {
@@ -36,41 +175,63 @@ pub fn list_devices() -> Result<DeviceList, AppError> {
         .and_then(|d| d.name().ok())
         .unwrap_or_default();
 
-    let mut inputs = Vec::new();
-    for (index, dev) in host
-        .input_devices()
-        .map_err(|e| AppError::Audio(format!("获取输入设备失败: {e}")))?
-        .enumerate()
-    {
-        let name = dev.name().unwrap_or_else(|_| "未知输入设备".to_string());
-        inputs.push(DeviceInfo {
-            id: make_device_id(&DeviceDirection::Input, index, &name),
-            name: name.clone(),
-            direction: DeviceDirection::Input,
-            is_default: name == default_input_name,
-            is_virtual_candidate: is_virtual_candidate(&name),
-        });
-    }
+    let describe = |(id, device, fingerprint): (String, cpal::Device, DeviceFingerprint),
+                    default_name: &str| {
+        let channels = match fingerprint.direction {
+            DeviceDirection::Input => device.default_input_config(),
+            DeviceDirection::Output => device.default_output_config(),
+        }
+        .map(|cfg| cfg.channels())
+        .unwrap_or(0);
+        DeviceInfo {
+            id,
+            name: fingerprint.name.clone(),
+            direction: fingerprint.direction,
+            is_default: fingerprint.name == default_name,
+            is_virtual_candidate: is_virtual_candidate(&fingerprint.name),
+            channels,
+            fingerprint,
+        }
+    };
 
-    let mut outputs = Vec::new();
-    for (index, dev) in host
-        .output_devices()
-        .map_err(|e| AppError::Audio(format!("获取输出设备失败: {e}")))?
-        .enumerate()
-    {
-        let name = dev.name().unwrap_or_else(|_| "未知输出设备".to_string());
-        outputs.push(DeviceInfo {
-            id: make_device_id(&DeviceDirection::Output, index, &name),
-            name: name.clone(),
-            direction: DeviceDirection::Output,
-            is_default: name == default_output_name,
-            is_virtual_candidate: is_virtual_candidate(&name),
-        });
-    }
+    let inputs = enumerate(&host, DeviceDirection::Input)?
+        .into_iter()
+        .map(|entry| describe(entry, &default_input_name))
+        .collect();
+    let outputs = enumerate(&host, DeviceDirection::Output)?
+        .into_iter()
+        .map(|entry| describe(entry, &default_output_name))
+        .collect();
 
     Ok(DeviceList { inputs, outputs })
 }
 
+/// 只读取设备名称与系统默认设备，不探测支持的格式，供设备监视线程高频轮询
+pub fn device_summary() -> Result<DeviceSummary, AppError> {
+    let host = host();
+    let names = |device: cpal::Device| device.name().unwrap_or_default();
+    Ok(DeviceSummary {
+        inputs: host
+            .input_devices()
+            .map_err(|e| AppError::Audio(format!("获取输入设备失败: {e}")))?
+            .map(names)
+            .collect(),
+        outputs: host
+            .output_devices()
+            .map_err(|e| AppError::Audio(format!("获取输出设备失败: {e}")))?
+            .map(names)
+            .collect(),
+        default_input: host
+            .default_input_device()
+            .and_then(|d| d.name().ok())
+            .unwrap_or_default(),
+        default_output: host
+            .default_output_device()
+            .and_then(|d| d.name().ok())
+            .unwrap_or_default(),
+    })
+}
+
 fn pick_default_input(devices: &DeviceList) -> Option<&DeviceInfo> {
     devices
         .inputs
@@ -89,17 +250,19 @@ fn pick_virtual_output(devices: &DeviceList) -> Option<&DeviceInfo> {
         .or_else(|| devices.outputs.first())
 }
 
-pub fn complete_route_defaults(route: &mut AudioRouteConfig) -> Result<(), AppError> {
-    let devices = list_devices()?;
-
+/// 为空的输入/桥接输出 id 填入默认设备
+pub fn complete_route_defaults(
+    route: &mut AudioRouteConfig,
+    devices: &DeviceList,
+) -> Result<(), AppError> {
     if route.input_device_id.is_empty() {
-        let input = pick_default_input(&devices)
+        let input = pick_default_input(devices)
             .ok_or_else(|| AppError::DeviceNotFound("未检测到可用物理输入设备".to_string()))?;
         route.input_device_id = input.id.clone();
     }
 
     if route.bridge_output_device_id.is_empty() {
-        let output = pick_virtual_output(&devices)
+        let output = pick_virtual_output(devices)
             .ok_or_else(|| AppError::DeviceNotFound("未检测到可用输出设备".to_string()))?;
         route.bridge_output_device_id = output.id.clone();
     }
@@ -107,81 +270,71 @@ pub fn complete_route_defaults(route: &mut AudioRouteConfig) -> Result<(), AppEr
     Ok(())
 }
 
+/// 按稳定 id 找到设备；id 失效时由调用方先按指纹重新绑定
 #[cfg(target_os = "windows")]
 fn resolve_device(device_id: &str, direction: DeviceDirection) -> Result<cpal::Device, AppError> {
-    let host = host();
-    let parts: Vec<&str> = device_id.split('#').collect();
-    if parts.len() < 3 {
-        return Err(AppError::InvalidArgument(format!(
-            "设备 ID 格式非法: {device_id}"
-        )));
-    }
-
-    let idx: usize = parts[1]
-        .parse()
-        .map_err(|_| AppError::InvalidArgument(format!("设备 ID 索引非法: {device_id}")))?;
-    let name = parts[2..].join("#");
-
-    let mut devices = match direction {
-        DeviceDirection::Input => host
-            .input_devices()
-            .map_err(|e| AppError::Audio(format!("读取输入设备失败: {e}")))?
-            .collect::<Vec<_>>(),
-        DeviceDirection::Output => host
-            .output_devices()
-            .map_err(|e| AppError::Audio(format!("读取输出设备失败: {e}")))?
-            .collect::<Vec<_>>(),
-    };
-
-    if idx < devices.len() {
-        let candidate = devices.swap_remove(idx);
-        let candidate_name = candidate.name().unwrap_or_default();
-        if candidate_name == name {
-            return Ok(candidate);
-        }
-    }
-
-    match devices
+    enumerate(&host(), direction)?
         .into_iter()
-        .find(|d| d.name().map(|n| n == name).unwrap_or(false))
-    {
-        Some(device) => Ok(device),
-        None => Err(AppError::DeviceNotFound(name)),
-    }
+        .find(|(id, _, _)| id == device_id)
+        .map(|(_, device, _)| device)
+        .ok_or_else(|| AppError::DeviceNotFound(device_id.to_string()))
 }
 
 #[cfg(target_os = "windows")]
 mod runtime_impl {
     use std::{
-        collections::VecDeque,
         sync::{
-            atomic::{AtomicU64, Ordering},
+            atomic::{AtomicI32, AtomicU32, Ordering},
             Arc,
         },
+        thread,
+        time::{Duration, Instant},
     };
 
     use cpal::{
         traits::{DeviceTrait, StreamTrait},
-        Device, SampleFormat, Stream, StreamConfig,
+        Device, FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
     };
     use parking_lot::Mutex;
 
     use crate::{
+        aec::{reference_feed, EchoCanceller, ReferenceFeed},
         audio::resolve_device,
+        bridge::{bridge, BridgeMonitor, BridgeProducer},
+        channels::{ChannelMatrix, InputChannelSelector},
+        dsp::{
+            chain::{processor_chain, ChainController, ProcessorChain},
+            shaping::{voice_shaper, ShaperController, StagedShaping, VoiceShaper},
+        },
         error::AppError,
-        gate::{apply_envelope, GateController},
-        types::{DeviceDirection, EngineState, RuntimeStatus},
+        gate::{GainEnvelope, GateController},
+        input_switch::{input_switch, Incoming, InputSwitch, SwitchControl, SwitchSignal},
+        jitter::JitterBuffer,
+        meter::{LevelMeter, LevelMonitor},
+        muted_speech::MutedSpeechDetector,
+        preroll::PreRoll,
+        resampler::{PushResampler, Resampler},
+        sample_format::{format_rank, integer_bits, TpdfDither},
+        types::{
+            AudioRouteConfig, DeviceDirection, DspChainConfig, EngineConfig, EngineState,
+            RuntimeStatus, VoiceShapingConfig,
+        },
+        vad::VoiceActivityDetector,
+        vox::VoxDetector,
     };
 
+    /// 桥接缓冲预分配时长
+    const BRIDGE_CAPACITY_MS: usize = 2000;
+
     fn choose_input_config(input: &Device) -> Result<cpal::SupportedStreamConfig, AppError> {
         let configs = input
             .supported_input_configs()
             .map_err(|e| AppError::Audio(format!("读取输入配置失败: {e}")))?;
         configs
-            .filter(|cfg| cfg.sample_format() == SampleFormat::F32)
-            .max_by_key(|cfg| cfg.max_sample_rate().0)
-            .map(|cfg| cfg.with_max_sample_rate())
-            .ok_or_else(|| AppError::Audio("输入设备不支持 F32 格式".to_string()))
+            .filter_map(|cfg| format_rank(cfg.sample_format()).map(|rank| (rank, cfg)))
+            .max_by_key(|(rank, cfg)| (*rank, cfg.max_sample_rate().0))
+            .map(|(_, cfg)| cfg.with_max_sample_rate())
+            .ok_or_else(|| AppError::Audio("输入设备没有可用的采样格式".to_string()))
     }
 
     fn choose_output_config(
@@ -193,122 +346,499 @@ mod runtime_impl {
         let configs: Vec<_> = output
             .supported_output_configs()
             .map_err(|e| AppError::Audio(format!("读取输出配置失败: {e}")))?
+            .filter_map(|cfg| format_rank(cfg.sample_format()).map(|rank| (rank, cfg)))
             .collect();
 
-        let exact_rate = configs.iter().find(|cfg| {
-            cfg.sample_format() == SampleFormat::F32
-                && cfg.min_sample_rate().0 <= target_rate
-                && cfg.max_sample_rate().0 >= target_rate
-        });
+        let exact_rate = configs
+            .iter()
+            .filter(|(_, cfg)| {
+                cfg.min_sample_rate().0 <= target_rate && cfg.max_sample_rate().0 >= target_rate
+            })
+            .max_by_key(|(rank, _)| *rank);
 
-        if let Some(cfg) = exact_rate {
+        if let Some((_, cfg)) = exact_rate {
             return Ok(cfg.with_sample_rate(cpal::SampleRate(target_rate)));
         }
 
         configs
             .into_iter()
-            .find(|cfg| cfg.sample_format() == SampleFormat::F32)
-            .map(|cfg| cfg.with_max_sample_rate())
-            .ok_or_else(|| AppError::Audio("输出设备不支持 F32 格式".to_string()))
+            .max_by_key(|(rank, cfg)| (*rank, cfg.max_sample_rate().0))
+            .map(|(_, cfg)| cfg.with_max_sample_rate())
+            .ok_or_else(|| AppError::Audio("输出设备没有可用的采样格式".to_string()))
+    }
+
+    fn build_input<T>(
+        device: &Device,
+        config: &StreamConfig,
+        mut on_data: impl FnMut(&[f32]) + Send + 'static,
+        on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> Result<Stream, cpal::BuildStreamError>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let mut converted = Vec::<f32>::with_capacity(config.sample_rate.0 as usize);
+        device.build_input_stream(
+            config,
+            move |data: &[T], _| {
+                converted.clear();
+                converted.extend(data.iter().map(|s| s.to_sample::<f32>()));
+                on_data(&converted);
+            },
+            on_error,
+            None,
+        )
+    }
+
+    fn build_output<T>(
+        device: &Device,
+        config: &StreamConfig,
+        dither_bits: Option<u32>,
+        mut render: impl FnMut(&mut [f32]) + Send + 'static,
+        on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> Result<Stream, cpal::BuildStreamError>
+    where
+        T: SizedSample + FromSample<f32>,
+    {
+        let mut rendered = Vec::<f32>::with_capacity(config.sample_rate.0 as usize);
+        let mut dither = dither_bits.map(TpdfDither::new);
+        device.build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                rendered.resize(data.len(), 0.0);
+                render(&mut rendered);
+                if let Some(dither) = dither.as_mut() {
+                    dither.apply(&mut rendered);
+                }
+                for (out, sample) in data.iter_mut().zip(&rendered) {
+                    *out = T::from_sample(*sample);
+                }
+            },
+            on_error,
+            None,
+        )
+    }
+
+    /// 按设备协商出的采样格式创建输入流，回调内统一转换为 f32
+    fn open_input_stream(
+        device: &Device,
+        cfg: &cpal::SupportedStreamConfig,
+        on_data: impl FnMut(&[f32]) + Send + 'static,
+        on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> Result<Stream, AppError> {
+        let config = cfg.config();
+        let result = match cfg.sample_format() {
+            SampleFormat::F32 => build_input::<f32>(device, &config, on_data, on_error),
+            SampleFormat::F64 => build_input::<f64>(device, &config, on_data, on_error),
+            SampleFormat::I32 => build_input::<i32>(device, &config, on_data, on_error),
+            SampleFormat::I16 => build_input::<i16>(device, &config, on_data, on_error),
+            SampleFormat::U16 => build_input::<u16>(device, &config, on_data, on_error),
+            SampleFormat::I8 => build_input::<i8>(device, &config, on_data, on_error),
+            SampleFormat::U8 => build_input::<u8>(device, &config, on_data, on_error),
+            other => {
+                return Err(AppError::Audio(format!("不支持的输入采样格式: {other}")));
+            }
+        };
+        result.map_err(|e| AppError::Audio(format!("创建输入流失败: {e}")))
+    }
+
+    /// 按设备协商出的采样格式创建输出流，整数格式写入前加 TPDF 抖动
+    fn open_output_stream(
+        device: &Device,
+        cfg: &cpal::SupportedStreamConfig,
+        render: impl FnMut(&mut [f32]) + Send + 'static,
+        on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> Result<Stream, AppError> {
+        let config = cfg.config();
+        let bits = integer_bits(cfg.sample_format());
+        let result = match cfg.sample_format() {
+            SampleFormat::F32 => build_output::<f32>(device, &config, bits, render, on_error),
+            SampleFormat::F64 => build_output::<f64>(device, &config, bits, render, on_error),
+            SampleFormat::I32 => build_output::<i32>(device, &config, bits, render, on_error),
+            SampleFormat::I16 => build_output::<i16>(device, &config, bits, render, on_error),
+            SampleFormat::U16 => build_output::<u16>(device, &config, bits, render, on_error),
+            SampleFormat::I8 => build_output::<i8>(device, &config, bits, render, on_error),
+            SampleFormat::U8 => build_output::<u8>(device, &config, bits, render, on_error),
+            other => {
+                return Err(AppError::Audio(format!("不支持的输出采样格式: {other}")));
+            }
+        };
+        result.map_err(|e| AppError::Audio(format!("创建输出流失败: {e}")))
+    }
+
+    /// 引擎启动时第一路输入流的编号，之后每次切换输入设备递增
+    const FIRST_SOURCE: u64 = 1;
+    /// 切换输入设备时等待新输入流接手的最长时间
+    const SWITCH_TIMEOUT: Duration = Duration::from_secs(2);
+    /// 取消切换后等待音频线程确认的最长时间
+    const CANCEL_TIMEOUT: Duration = Duration::from_millis(200);
+
+    /// 回声消除：每个处理声道一个消除器，共用同一路参考
+    struct EchoStage {
+        cancellers: Vec<EchoCanceller>,
+        feed: ReferenceFeed,
+        /// 按块取出的参考信号
+        reference: Vec<f32>,
+        /// 从交错块中拆出的单个声道
+        lane: Vec<f32>,
+    }
+
+    /// 声道选择之后的输入处理：回声消除、音色、处理链、电平与检测器，最后写入桥接缓冲。
+    ///
+    /// 同一时刻只由一路输入流回调持有；切换输入设备时整体交给新输入流，状态保持不变。
+    struct InputPipeline {
+        switch: InputSwitch<Box<InputPipeline>>,
+        channels: usize,
+        aec: Option<EchoStage>,
+        voice: VoiceShaper,
+        chain: ProcessorChain,
+        pre_meter: LevelMeter,
+        muted_speech: MutedSpeechDetector,
+        vox: VoxDetector,
+        vad: VoiceActivityDetector,
+        /// 多声道时供检测器使用的单声道混合
+        detect_scratch: Vec<f32>,
+        levels: Arc<LevelMonitor>,
+        gate: Arc<GateController>,
+        /// 语音概率，按 f32 位模式存放
+        speech_probability: Arc<AtomicU32>,
+        producer: BridgeProducer,
+    }
+
+    impl InputPipeline {
+        fn process(&mut self, block: &mut [f32]) {
+            let channels = self.channels;
+            if let Some(EchoStage {
+                cancellers,
+                feed,
+                reference,
+                lane,
+            }) = self.aec.as_mut()
+            {
+                for chunk in block.chunks_mut(reference.len() * channels) {
+                    let frames = chunk.len() / channels;
+                    let reference = &mut reference[..frames];
+                    feed.pull(reference);
+                    // 逐声道拆出、消除回声后写回交错块
+                    for (c, canceller) in cancellers.iter_mut().enumerate() {
+                        let lane = &mut lane[..frames];
+                        for (value, frame) in lane.iter_mut().zip(chunk.chunks_exact(channels)) {
+                            *value = frame[c];
+                        }
+                        canceller.process(lane, reference);
+                        for (value, frame) in lane.iter().zip(chunk.chunks_exact_mut(channels)) {
+                            frame[c] = *value;
+                        }
+                    }
+                }
+            }
+            self.voice.process(block);
+            self.chain.process(block);
+            self.pre_meter.process(block, &self.levels.pre_gate);
+            let frames = block.len() / channels;
+            if self
+                .muted_speech
+                .process(frames, self.pre_meter.rms_db(), self.gate.is_open())
+            {
+                self.levels.flag_muted_speech();
+            }
+            let detect = if channels == 1 {
+                &*block
+            } else {
+                self.detect_scratch.clear();
+                self.detect_scratch.extend(
+                    block
+                        .chunks_exact(channels)
+                        .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+                );
+                &self.detect_scratch
+            };
+            // 仅在判定翻转时通知门控，避免每个回调都唤醒应用线程
+            if let Some(open) = self.vox.process(detect) {
+                self.gate.vox_decision(open);
+            }
+            if let Some(open) = self.vad.process(detect) {
+                self.gate.vad_decision(open);
+            }
+            self.speech_probability
+                .store(self.vad.probability().to_bits(), Ordering::Relaxed);
+            self.producer.push(block);
+        }
+    }
+
+    fn input_selector(
+        route: &AudioRouteConfig,
+        channels: usize,
+        sample_rate: u32,
+    ) -> Result<InputChannelSelector, AppError> {
+        if route.passthrough {
+            Ok(InputChannelSelector::passthrough(channels))
+        } else {
+            InputChannelSelector::new(&route.input_channel, channels, sample_rate)
+        }
+    }
+
+    /// 输入流拿到处理链的方式
+    enum SourceRole {
+        /// 引擎启动时的第一路输入流，直接持有处理链
+        First(Box<InputPipeline>),
+        /// 切换时打开的输入流，先暂存数据，旧输入流交出处理链后接手并启用新的音色参数
+        Incoming(Incoming<Box<InputPipeline>>, Box<StagedShaping>),
+    }
+
+    /// 打开一路输入流：声道选择，采样率与处理链不同时先重采样，再送入输入处理
+    fn open_source(
+        device: &Device,
+        cfg: &cpal::SupportedStreamConfig,
+        mut selector: InputChannelSelector,
+        pipeline_rate: u32,
+        role: SourceRole,
+        signal: Arc<SwitchSignal>,
+        last_error: Arc<Mutex<Option<String>>>,
+    ) -> Result<Stream, AppError> {
+        let (source, mut pipeline, mut incoming, mut staged) = match role {
+            SourceRole::First(pipeline) => (FIRST_SOURCE, Some(pipeline), None, None),
+            SourceRole::Incoming(incoming, shaping) => {
+                (incoming.source(), None, Some(incoming), Some(*shaping))
+            }
+        };
+        let rate = cfg.sample_rate().0;
+        let channels = selector.output_channels();
+        let mut resampler =
+            (rate != pipeline_rate).then(|| PushResampler::new(rate, pipeline_rate, channels));
+        // 回调内的临时缓冲预先分配，避免实时线程申请内存
+        let mut selected = Vec::<f32>::with_capacity(rate as usize * channels);
+        let mut converted = Vec::<f32>::with_capacity(pipeline_rate as usize * channels);
+        open_input_stream(
+            device,
+            cfg,
+            move |data: &[f32]| {
+                selector.process(data, &mut selected);
+                let block = match resampler.as_mut() {
+                    Some(resampler) => {
+                        resampler.process(&selected, &mut converted);
+                        &mut converted
+                    }
+                    None => &mut selected,
+                };
+                if pipeline.is_none() {
+                    pipeline = incoming.as_mut().and_then(|incoming| incoming.offer(block));
+                    if let (Some(current), Some(shaping)) = (pipeline.as_mut(), staged.take()) {
+                        current.voice.apply_staged(shaping);
+                    }
+                }
+                let Some(current) = pipeline.as_mut() else {
+                    return;
+                };
+                let handover = current.switch.mix(block);
+                current.process(block);
+                if let Some(handover) = handover {
+                    if let Some(current) = pipeline.take() {
+                        handover.complete(current);
+                    }
+                }
+            },
+            move |err| {
+                // 已被切走的输入流出错不影响引擎
+                if signal.active() == source {
+                    *last_error.lock() = Some(format!("输入流错误: {err}"));
+                }
+            },
+        )
     }
 
     pub struct EngineRuntime {
         input_stream: Stream,
         output_stream: Stream,
-        buffer: Arc<Mutex<VecDeque<f32>>>,
-        sample_rate: u32,
-        xruns: Arc<AtomicU64>,
+        /// 回声消除参考的环回采集流
+        reference_stream: Option<Stream>,
+        monitor: BridgeMonitor,
+        drift_ppm: Arc<AtomicI32>,
+        target_latency_ms: u32,
+        input_sample_rate: u32,
+        output_sample_rate: u32,
+        resample_ratio: f64,
+        /// 语音概率，按 f32 位模式存放
+        speech_probability: Arc<AtomicU32>,
+        dsp: ChainController,
+        shaper: ShaperController,
+        aec_latency: usize,
+        /// 输入源切换的控制端；处理链本身随输入流回调移动
+        switch: SwitchControl<Box<InputPipeline>>,
+        /// 处理链路上的声道数
+        channels: usize,
+        next_source: u64,
+        input_device_id: String,
+        output_device_id: String,
         last_error: Arc<Mutex<Option<String>>>,
     }
 
     impl EngineRuntime {
         pub fn start(
-            input_id: &str,
-            output_id: &str,
+            route: &AudioRouteConfig,
+            engine: &EngineConfig,
+            dsp: &DspChainConfig,
+            shaping: &VoiceShapingConfig,
             gate: Arc<GateController>,
+            levels: Arc<LevelMonitor>,
         ) -> Result<Self, AppError> {
-            let input = resolve_device(input_id, DeviceDirection::Input)?;
-            let output = resolve_device(output_id, DeviceDirection::Output)?;
+            let input = resolve_device(&route.input_device_id, DeviceDirection::Input)?;
+            let output = resolve_device(&route.bridge_output_device_id, DeviceDirection::Output)?;
 
             let in_cfg = choose_input_config(&input)?;
             let out_cfg = choose_output_config(&output, &in_cfg)?;
 
-            let sample_rate = out_cfg.sample_rate().0;
+            let input_sample_rate = in_cfg.sample_rate().0;
+            let output_sample_rate = out_cfg.sample_rate().0;
             let input_channels = usize::from(in_cfg.channels()).max(1);
             let output_channels = usize::from(out_cfg.channels()).max(1);
 
-            let buffer = Arc::new(Mutex::new(VecDeque::<f32>::with_capacity(
-                sample_rate as usize,
-            )));
-            let xruns = Arc::new(AtomicU64::new(0));
+            let selector = input_selector(route, input_channels, input_sample_rate)?;
+            // 处理链路上的声道数：直通时沿用输入布局，否则为单声道
+            let channels = selector.output_channels();
+            let matrix = ChannelMatrix::new(channels, &route.output_channels, output_channels)?;
+
+            // 桥接缓冲按输入采样率计量，重采样在输出回调内完成
+            let capacity = input_sample_rate as usize * BRIDGE_CAPACITY_MS / 1000;
+            let (producer, consumer, monitor) = bridge(capacity, channels);
+            let resampler = Resampler::new(input_sample_rate, output_sample_rate, channels);
+            let resample_ratio = resampler.ratio();
+            let mut jitter = JitterBuffer::new(
+                consumer,
+                resampler,
+                input_sample_rate,
+                engine.target_latency_ms,
+            );
+            let target_latency_ms = jitter.target_latency_ms();
+            let drift_ppm = jitter.correction_handle();
             let last_error = Arc::new(Mutex::new(None::<String>));
 
-            let in_buffer = buffer.clone();
-            let in_last_error = last_error.clone();
-            let input_config: StreamConfig = in_cfg.config();
-            let input_stream = input
-                .build_input_stream(
-                    &input_config,
-                    move |data: &[f32], _| {
-                        let mut queue = in_buffer.lock();
-                        for frame in data.chunks(input_channels) {
-                            if frame.is_empty() {
-                                continue;
-                            }
-                            let sum: f32 = frame.iter().copied().sum();
-                            let mono = sum / frame.len() as f32;
-                            if queue.len() > 240_000 {
-                                let _ = queue.pop_front();
-                            }
-                            queue.push_back(mono);
-                        }
+            log::info!(
+                "音频格式协商: 输入 {} {}Hz {}ch, 输出 {} {}Hz {}ch, 处理 {}ch",
+                in_cfg.sample_format(),
+                input_sample_rate,
+                input_channels,
+                out_cfg.sample_format(),
+                output_sample_rate,
+                output_channels,
+                channels
+            );
+
+            // 回声消除：在参考输出设备上做环回采集，按输入采样率对齐后送入消除器
+            let mut aec = None;
+            let mut reference_stream = None;
+            if !route.reference_output_device_id.is_empty() {
+                let reference =
+                    resolve_device(&route.reference_output_device_id, DeviceDirection::Output)?;
+                let ref_cfg = reference
+                    .default_output_config()
+                    .map_err(|e| AppError::Audio(format!("读取回声参考设备配置失败: {e}")))?;
+                let ref_channels = usize::from(ref_cfg.channels()).max(1);
+                let (mut ref_producer, feed) =
+                    reference_feed(ref_cfg.sample_rate().0, input_sample_rate);
+                let mut ref_scratch = Vec::<f32>::with_capacity(ref_cfg.sample_rate().0 as usize);
+                let ref_last_error = last_error.clone();
+                let stream = open_input_stream(
+                    &reference,
+                    &ref_cfg,
+                    move |data: &[f32]| {
+                        ref_scratch.clear();
+                        ref_scratch.extend(
+                            data.chunks(ref_channels)
+                                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32),
+                        );
+                        ref_producer.push(&ref_scratch);
                     },
                     move |err| {
-                        *in_last_error.lock() = Some(format!("输入流错误: {err}"));
+                        *ref_last_error.lock() = Some(format!("回声参考流错误: {err}"));
                     },
-                    None,
-                )
-                .map_err(|e| AppError::Audio(format!("创建输入流失败: {e}")))?;
+                )?;
+                // 每个处理声道一个消除器，共用同一路参考
+                let cancellers: Vec<_> = (0..channels)
+                    .map(|_| EchoCanceller::new(input_sample_rate))
+                    .collect();
+                log::info!(
+                    "回声消除已启用: 参考 {}Hz {}ch, 块长 {} 样本",
+                    ref_cfg.sample_rate().0,
+                    ref_channels,
+                    cancellers[0].latency()
+                );
+                let block = vec![0.0_f32; input_sample_rate as usize];
+                aec = Some(EchoStage {
+                    cancellers,
+                    feed,
+                    reference: block.clone(),
+                    lane: block,
+                });
+                reference_stream = Some(stream);
+            }
+            let aec_latency = aec
+                .as_ref()
+                .map_or(0, |stage| stage.cancellers[0].latency());
+
+            // 系数按本次协商出的输入采样率计算
+            let (shaper, voice) = voice_shaper(shaping, input_sample_rate, channels)?;
+            let (dsp, chain) = processor_chain(dsp, input_sample_rate, channels)?;
+            let vox = VoxDetector::new(&engine.vox, input_sample_rate);
+            let vad = VoiceActivityDetector::new(&engine.vad, input_sample_rate);
+            // 新检测器从闭麦开始，同步门控中残留的上次判定
+            gate.vox_decision(false);
+            gate.vad_decision(false);
+            let speech_probability = Arc::new(AtomicU32::new(0));
+            let (switch, input_switch) = input_switch(FIRST_SOURCE, input_sample_rate, channels);
+            let pipeline = Box::new(InputPipeline {
+                switch: input_switch,
+                channels,
+                aec,
+                voice,
+                chain,
+                // 电平计按样本流计时，交错多声道时样本率为采样率乘以声道数
+                pre_meter: LevelMeter::new(input_sample_rate * channels as u32),
+                muted_speech: MutedSpeechDetector::new(&engine.muted_speech, input_sample_rate),
+                vox,
+                vad,
+                detect_scratch: Vec::with_capacity(input_sample_rate as usize),
+                levels: levels.clone(),
+                gate: gate.clone(),
+                speech_probability: speech_probability.clone(),
+                producer,
+            });
+            let input_stream = open_source(
+                &input,
+                &in_cfg,
+                selector,
+                input_sample_rate,
+                SourceRole::First(pipeline),
+                switch.signal(),
+                last_error.clone(),
+            )?;
 
-            let out_buffer = buffer.clone();
+            let mut out_scratch = Vec::<f32>::with_capacity(output_sample_rate as usize * channels);
+            let mut preroll = PreRoll::new(engine.preroll_ms, output_sample_rate, channels);
+            let mut envelope = GainEnvelope::new(&engine.envelope, output_sample_rate, channels);
+            let mut post_meter = LevelMeter::new(output_sample_rate * channels as u32);
             let out_gate = gate.clone();
-            let out_xruns = xruns.clone();
             let out_last_error = last_error.clone();
-            let out_sample_rate = sample_rate;
-            let output_config: StreamConfig = out_cfg.config();
-            let output_stream = output
-                .build_output_stream(
-                    &output_config,
-                    move |data: &mut [f32], _| {
-                        let open = out_gate.is_open();
-                        let mut queue = out_buffer.lock();
-                        let frame_count = data.len() / output_channels;
-                        let mut gain = if open { 1.0 } else { 0.0 };
-                        apply_envelope(&mut gain, open, frame_count, out_sample_rate);
-
-                        for frame in data.chunks_mut(output_channels) {
-                            let mono = if open {
-                                if let Some(v) = queue.pop_front() {
-                                    v * gain
-                                } else {
-                                    out_xruns.fetch_add(1, Ordering::Relaxed);
-                                    0.0
-                                }
-                            } else {
-                                0.0
-                            };
-
-                            for sample in frame.iter_mut() {
-                                *sample = mono;
-                            }
-                        }
-                    },
-                    move |err| {
-                        *out_last_error.lock() = Some(format!("输出流错误: {err}"));
-                    },
-                    None,
-                )
-                .map_err(|e| AppError::Audio(format!("创建输出流失败: {e}")))?;
+            let output_stream = open_output_stream(
+                &output,
+                &out_cfg,
+                move |data: &mut [f32]| {
+                    let frame_count = data.len() / matrix.channels();
+
+                    // 闭麦时同样消费桥接数据，避免开麦瞬间播放积压的旧音频
+                    out_scratch.resize(frame_count * channels, 0.0);
+                    jitter.render(&mut out_scratch);
+                    let open = out_gate.is_open();
+                    preroll.process(open, &mut out_scratch);
+                    envelope.process(open, &mut out_scratch);
+                    post_meter.process(&out_scratch, &levels.post_gate);
+
+                    matrix.write(data, &out_scratch);
+                },
+                move |err| {
+                    *out_last_error.lock() = Some(format!("输出流错误: {err}"));
+                },
+            )?;
 
             input_stream
                 .play()
@@ -316,31 +846,154 @@ mod runtime_impl {
             output_stream
                 .play()
                 .map_err(|e| AppError::Audio(format!("启动输出流失败: {e}")))?;
+            if let Some(stream) = reference_stream.as_ref() {
+                stream
+                    .play()
+                    .map_err(|e| AppError::Audio(format!("启动回声参考流失败: {e}")))?;
+            }
 
             Ok(Self {
                 input_stream,
                 output_stream,
-                buffer,
-                sample_rate,
-                xruns,
+                reference_stream,
+                monitor,
+                drift_ppm,
+                target_latency_ms,
+                input_sample_rate,
+                output_sample_rate,
+                resample_ratio,
+                speech_probability,
+                dsp,
+                shaper,
+                aec_latency,
+                switch,
+                channels,
+                next_source: FIRST_SOURCE + 1,
+                input_device_id: route.input_device_id.clone(),
+                output_device_id: route.bridge_output_device_id.clone(),
                 last_error,
             })
         }
 
+        /// 不重启引擎更新处理链：参数、旁路与顺序均在下一个输入块生效
+        pub fn update_dsp(&mut self, config: &DspChainConfig) -> Result<(), AppError> {
+            self.dsp.apply(config)
+        }
+
+        /// 不重启引擎更新输入增益、高通与均衡
+        pub fn update_shaping(&mut self, config: &VoiceShapingConfig) -> Result<(), AppError> {
+            self.shaper.apply(config)
+        }
+
+        /// 输出流与处理链保持运行，只把输入流换到 `route` 中的输入设备与声道，两路输入交叉淡化。
+        ///
+        /// 切换期间旧输入流持续写入桥接缓冲直到淡化结束，输出流不会断流。新设备采样率不同时在
+        /// 输入端重采样到处理链的采样率；直通模式下声道数不同、或旧设备已不再出数等不到交接时
+        /// 切换失败，由调用方改为重启引擎。
+        pub fn switch_input(
+            &mut self,
+            route: &AudioRouteConfig,
+            shaping: &VoiceShapingConfig,
+        ) -> Result<(), AppError> {
+            let device = resolve_device(&route.input_device_id, DeviceDirection::Input)?;
+            let cfg = choose_input_config(&device)?;
+            let device_channels = usize::from(cfg.channels()).max(1);
+            let selector = input_selector(route, device_channels, cfg.sample_rate().0)?;
+            if selector.output_channels() != self.channels {
+                return Err(AppError::Audio(format!(
+                    "新输入设备为 {}ch，与当前处理链的 {}ch 不一致",
+                    selector.output_channels(),
+                    self.channels
+                )));
+            }
+
+            // 新设备的音色参数随交接一起生效，切换失败时保持原参数
+            let staged = self.shaper.stage(shaping)?;
+            let source = self.next_source;
+            self.next_source += 1;
+            let incoming = self.switch.prepare(source);
+            let stream = open_source(
+                &device,
+                &cfg,
+                selector,
+                self.input_sample_rate,
+                SourceRole::Incoming(incoming, Box::new(staged)),
+                self.switch.signal(),
+                self.last_error.clone(),
+            )?;
+            stream
+                .play()
+                .map_err(|e| AppError::Audio(format!("启动输入流失败: {e}")))?;
+            if !self.switch.begin() {
+                return Err(AppError::Audio("音频线程未响应切换请求".to_string()));
+            }
+
+            // 等待交叉淡化结束、新输入流接手处理链；只轮询原子变量，不与音频线程争锁
+            let deadline = Instant::now() + SWITCH_TIMEOUT;
+            while self.switch.active() != source && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+            if self.switch.active() != source {
+                self.switch.cancel();
+                // 等音频线程放下新输入流的暂存端后再关闭新输入流
+                let deadline = Instant::now() + CANCEL_TIMEOUT;
+                while self.switch.is_pending() && Instant::now() < deadline {
+                    thread::sleep(Duration::from_millis(5));
+                }
+                // 取消前恰好完成交接时按成功处理
+                if self.switch.active() != source {
+                    return Err(AppError::Audio("切换输入设备超时".to_string()));
+                }
+            }
+
+            let previous = std::mem::replace(&mut self.input_stream, stream);
+            let _ = previous.pause();
+            drop(previous);
+            self.switch.collect_garbage();
+            self.input_device_id = route.input_device_id.clone();
+            log::info!(
+                "输入设备已切换: {} {}Hz {}ch",
+                cfg.sample_format(),
+                cfg.sample_rate().0,
+                device_channels
+            );
+            Ok(())
+        }
+
         pub fn status(&self, gate_state: crate::types::GateState) -> RuntimeStatus {
-            let queue_len = self.buffer.lock().len() as u64;
-            let samples_per_ms = (self.sample_rate as u64) / 1000;
-            let buffer_level_ms = if samples_per_ms == 0 {
+            let queued = self.monitor.occupied() as u64;
+            let buffer_level_ms = if self.input_sample_rate == 0 {
                 0
             } else {
-                (queue_len / samples_per_ms) as u32
+                (queued * 1000 / self.input_sample_rate as u64) as u32
             };
+            let overflows = self.monitor.overflows();
+            let underflows = self.monitor.underflows();
+            let last_error = self.last_error.lock().clone();
 
             RuntimeStatus {
-                engine_state: EngineState::Running,
+                // 流错误回调触发后流已不可用，报告为错误状态，由上层重启引擎
+                engine_state: if last_error.is_some() {
+                    EngineState::Error
+                } else {
+                    EngineState::Running
+                },
                 buffer_level_ms,
-                xruns: self.xruns.load(Ordering::Relaxed),
-                last_error: self.last_error.lock().clone(),
+                xruns: overflows + underflows,
+                overflows,
+                underflows,
+                target_latency_ms: self.target_latency_ms,
+                drift_ppm: self.drift_ppm.load(Ordering::Relaxed),
+                input_sample_rate: self.input_sample_rate,
+                output_sample_rate: self.output_sample_rate,
+                resample_ratio: self.resample_ratio,
+                dsp_latency_ms: (self.dsp.latency_samples() + self.aec_latency) as f32 * 1000.0
+                    / self.input_sample_rate.max(1) as f32,
+                speech_probability: f32::from_bits(self.speech_probability.load(Ordering::Relaxed)),
+                gain_reduction_db: self.dsp.gain_reduction_db(),
+                active_input_device_id: self.input_device_id.clone(),
+                active_output_device_id: self.output_device_id.clone(),
+                last_error,
                 gate_state,
             }
         }
@@ -350,6 +1003,9 @@ mod runtime_impl {
         fn drop(&mut self) {
             let _ = self.input_stream.pause();
             let _ = self.output_stream.pause();
+            if let Some(stream) = self.reference_stream.as_ref() {
+                let _ = stream.pause();
+            }
         }
     }
 }
@@ -361,29 +1017,53 @@ mod runtime_impl {
     use crate::{
         error::AppError,
         gate::GateController,
-        types::{EngineState, RuntimeStatus},
+        meter::LevelMonitor,
+        types::{
+            AudioRouteConfig, DspChainConfig, EngineConfig, EngineState, RuntimeStatus,
+            VoiceShapingConfig,
+        },
     };
 
     pub struct EngineRuntime;
 
     impl EngineRuntime {
         pub fn start(
-            _input_id: &str,
-            _output_id: &str,
+            _route: &AudioRouteConfig,
+            _engine: &EngineConfig,
+            _dsp: &DspChainConfig,
+            _shaping: &VoiceShapingConfig,
             _gate: Arc<GateController>,
+            _levels: Arc<LevelMonitor>,
         ) -> Result<Self, AppError> {
             Err(AppError::System(
                 "当前平台仅提供开发桩实现，真实音频桥接仅支持 Windows".to_string(),
             ))
         }
 
+        pub fn update_dsp(&mut self, _config: &DspChainConfig) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        pub fn update_shaping(&mut self, _config: &VoiceShapingConfig) -> Result<(), AppError> {
+            Ok(())
+        }
+
+        pub fn switch_input(
+            &mut self,
+            _route: &AudioRouteConfig,
+            _shaping: &VoiceShapingConfig,
+        ) -> Result<(), AppError> {
+            Err(AppError::System(
+                "当前平台仅提供开发桩实现，真实音频桥接仅支持 Windows".to_string(),
+            ))
+        }
+
         pub fn status(&self, gate_state: crate::types::GateState) -> RuntimeStatus {
             RuntimeStatus {
                 engine_state: EngineState::Error,
-                buffer_level_ms: 0,
-                xruns: 0,
                 last_error: Some("非 Windows 平台未启用音频桥接".to_string()),
                 gate_state,
+                ..RuntimeStatus::default()
             }
         }
     }
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// 重启引擎的指数退避：第一次立即重试，之后每次等待时间翻倍，直到上限。
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            current: Duration::ZERO,
+        }
+    }
+
+    /// 返回本次重试前应等待的时间，并推进到下一档
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = if delay.is_zero() {
+            self.initial
+        } else {
+            (delay * 2).min(self.max)
+        };
+        delay
+    }
+
+    /// 引擎稳定运行后回到第一档
+    pub fn reset(&mut self) {
+        self.current = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_should_double_up_to_cap_and_reset() {
+        let mut backoff = Backoff::new(Duration::from_secs(2), Duration::from_secs(10));
+        let delays: Vec<u64> = (0..6).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [0, 2, 4, 8, 10, 10]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::ZERO);
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use ringbuf::{
+    traits::{Consumer, Observer, Producer, Split},
+    HeapCons, HeapProd, HeapRb, Obs,
+};
+
+/// 输入回调与输出回调之间的单生产者单消费者桥接缓冲。
+///
+/// 样本按 `channels` 声道交错存放，读写与计量都以整帧为单位，不会把一帧拆开。
+/// 容量（帧数）在创建时一次性分配，实时线程内不加锁、不分配内存。
+pub fn bridge(capacity: usize, channels: usize) -> (BridgeProducer, BridgeConsumer, BridgeMonitor) {
+    let channels = channels.max(1);
+    let rb = HeapRb::<f32>::new(capacity.max(1) * channels);
+    let (prod, cons) = rb.split();
+    let observer = prod.observe();
+    let consumer_observer = cons.observe();
+    let overflows = Arc::new(AtomicU64::new(0));
+    let underflows = Arc::new(AtomicU64::new(0));
+
+    (
+        BridgeProducer {
+            prod,
+            channels,
+            overflows: overflows.clone(),
+        },
+        BridgeConsumer {
+            cons,
+            channels,
+            observer: consumer_observer,
+            overflows: overflows.clone(),
+            underflows: underflows.clone(),
+        },
+        BridgeMonitor {
+            observer,
+            channels,
+            overflows,
+            underflows,
+        },
+    )
+}
+
+pub struct BridgeProducer {
+    prod: HeapProd<f32>,
+    channels: usize,
+    overflows: Arc<AtomicU64>,
+}
+
+impl BridgeProducer {
+    /// 写入一段交错样本；缓冲已满时丢弃放不下的整帧并记一次溢出。
+    pub fn push(&mut self, samples: &[f32]) {
+        let fit = self.prod.vacant_len() / self.channels * self.channels;
+        let written = self.prod.push_slice(&samples[..samples.len().min(fit)]);
+        if written < samples.len() {
+            self.overflows.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+pub struct BridgeConsumer {
+    cons: HeapCons<f32>,
+    channels: usize,
+    observer: Obs<Arc<HeapRb<f32>>>,
+    overflows: Arc<AtomicU64>,
+    underflows: Arc<AtomicU64>,
+}
+
+impl BridgeConsumer {
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// 读取交错样本填满 `out`，返回实际读到的帧数；不足时剩余部分补零并记一次欠载。
+    pub fn pop(&mut self, out: &mut [f32]) -> usize {
+        let read = self.cons.pop_slice(out);
+        if read < out.len() {
+            out[read..].fill(0.0);
+            self.underflows.fetch_add(1, Ordering::Relaxed);
+        }
+        read / self.channels
+    }
+
+    /// 已缓冲的帧数
+    pub fn occupied(&self) -> usize {
+        self.observer.occupied_len() / self.channels
+    }
+
+    /// 积压过多时直接丢弃最旧的 `count` 帧，计入溢出
+    pub fn discard(&mut self, count: usize) {
+        if self.cons.skip(count * self.channels) > 0 {
+            self.overflows.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 供状态线程读取桥接缓冲占用与计数，只读原子量。
+pub struct BridgeMonitor {
+    observer: Obs<Arc<HeapRb<f32>>>,
+    channels: usize,
+    overflows: Arc<AtomicU64>,
+    underflows: Arc<AtomicU64>,
+}
+
+impl BridgeMonitor {
+    /// 已缓冲的帧数
+    pub fn occupied(&self) -> usize {
+        self.observer.occupied_len() / self.channels
+    }
+
+    pub fn overflows(&self) -> u64 {
+        self.overflows.load(Ordering::Relaxed)
+    }
+
+    pub fn underflows(&self) -> u64 {
+        self.underflows.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bridge_should_count_overflow_and_underflow_separately() {
+        let (mut prod, mut cons, monitor) = bridge(4, 1);
+        prod.push(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(monitor.occupied(), 4);
+        assert_eq!(monitor.overflows(), 1);
+
+        let mut out = [0.0; 6];
+        cons.pop(&mut out);
+        assert_eq!(out, [1.0, 2.0, 3.0, 4.0, 0.0, 0.0]);
+        assert_eq!(monitor.occupied(), 0);
+        assert_eq!(monitor.underflows(), 1);
+        assert_eq!(monitor.overflows(), 1);
+    }
+
+    #[test]
+    fn bridge_should_keep_frames_whole() {
+        let (mut prod, mut cons, monitor) = bridge(2, 2);
+        // 放不下的第三帧整帧丢弃，不会留下半帧
+        prod.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(monitor.occupied(), 2);
+        assert_eq!(monitor.overflows(), 1);
+
+        cons.discard(1);
+        let mut out = [0.0; 4];
+        assert_eq!(cons.pop(&mut out), 1);
+        assert_eq!(out, [3.0, 4.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{error::AppError, types::InputChannel};
+
+/// 直通模式最多保留的声道数
+pub const MAX_PASSTHROUGH_CHANNELS: usize = 8;
+
+/// 自动选择声道时能量的平滑时间常数（毫秒）
+const AUTO_ENERGY_WINDOW_MS: f32 = 300.0;
+/// 其他声道能量需高出当前声道多少才切换（dB），避免在相近声道间来回跳
+const AUTO_SWITCH_MARGIN_DB: f32 = 6.0;
+/// 切换声道时的交叉淡化时长（毫秒）
+const AUTO_CROSSFADE_MS: f32 = 20.0;
+
+enum Selection {
+    Average,
+    Fixed(usize),
+    Auto,
+    /// 直通：保留前若干个声道
+    Passthrough(usize),
+}
+
+/// 从多声道输入帧中取出处理用的信号：指定声道、左右声道、平均、按能量自动选择，
+/// 或在直通模式下保留原声道布局。
+pub struct InputChannelSelector {
+    selection: Selection,
+    channels: usize,
+    /// 各声道的平滑能量，仅自动模式使用
+    energy: Vec<f32>,
+    energy_coeff: f32,
+    switch_ratio: f32,
+    current: usize,
+    previous: usize,
+    fade: usize,
+    fade_len: usize,
+}
+
+impl InputChannelSelector {
+    pub fn new(mode: &InputChannel, channels: usize, sample_rate: u32) -> Result<Self, AppError> {
+        let channels = channels.max(1);
+        let selection = match *mode {
+            InputChannel::Average => Selection::Average,
+            InputChannel::Left => Selection::Fixed(0),
+            // 单声道设备没有右声道，退回唯一的声道
+            InputChannel::Right => Selection::Fixed(1.min(channels - 1)),
+            InputChannel::Channel { index } => {
+                let index = usize::from(index);
+                if index >= channels {
+                    return Err(AppError::InvalidArgument(format!(
+                        "输入设备只有 {channels} 个声道，无法选择第 {} 声道",
+                        index + 1
+                    )));
+                }
+                Selection::Fixed(index)
+            }
+            InputChannel::Auto => Selection::Auto,
+        };
+        let rate = sample_rate.max(1) as f32;
+        Ok(Self {
+            selection,
+            channels,
+            energy: vec![0.0; channels],
+            energy_coeff: 1.0 - (-1000.0 / (AUTO_ENERGY_WINDOW_MS * rate)).exp(),
+            switch_ratio: 10.0_f32.powf(AUTO_SWITCH_MARGIN_DB / 10.0),
+            current: 0,
+            previous: 0,
+            fade: 0,
+            fade_len: ((AUTO_CROSSFADE_MS * rate / 1000.0) as usize).max(1),
+        })
+    }
+
+    /// 直通模式：保留输入的声道布局，超过上限的声道被丢弃
+    pub fn passthrough(channels: usize) -> Self {
+        let channels = channels.max(1);
+        Self {
+            selection: Selection::Passthrough(channels.min(MAX_PASSTHROUGH_CHANNELS)),
+            channels,
+            energy: Vec::new(),
+            energy_coeff: 0.0,
+            switch_ratio: 1.0,
+            current: 0,
+            previous: 0,
+            fade: 0,
+            fade_len: 1,
+        }
+    }
+
+    /// 输出的声道数：直通模式为保留的声道数，其余模式为 1
+    pub fn output_channels(&self) -> usize {
+        match self.selection {
+            Selection::Passthrough(kept) => kept,
+            _ => 1,
+        }
+    }
+
+    /// 当前使用的声道；平均与直通模式返回 None
+    pub fn active_channel(&self) -> Option<usize> {
+        match self.selection {
+            Selection::Average | Selection::Passthrough(_) => None,
+            Selection::Fixed(channel) => Some(channel),
+            Selection::Auto => Some(self.current),
+        }
+    }
+
+    /// 把交错的多声道数据按所选方式写入 `out`
+    pub fn process(&mut self, data: &[f32], out: &mut Vec<f32>) {
+        out.clear();
+        let frames = data.chunks_exact(self.channels);
+        match self.selection {
+            Selection::Average => {
+                let scale = 1.0 / self.channels as f32;
+                out.extend(frames.map(|frame| frame.iter().sum::<f32>() * scale));
+            }
+            Selection::Fixed(channel) => out.extend(frames.map(|frame| frame[channel])),
+            Selection::Passthrough(kept) => {
+                for frame in frames {
+                    out.extend_from_slice(&frame[..kept]);
+                }
+            }
+            Selection::Auto => {
+                self.pick_loudest();
+                for frame in frames {
+                    for (energy, sample) in self.energy.iter_mut().zip(frame) {
+                        *energy += (sample * sample - *energy) * self.energy_coeff;
+                    }
+                    let mut value = frame[self.current];
+                    if self.fade > 0 {
+                        let t = self.fade as f32 / self.fade_len as f32;
+                        value = value * (1.0 - t) + frame[self.previous] * t;
+                        self.fade -= 1;
+                    }
+                    out.push(value);
+                }
+            }
+        }
+    }
+
+    /// 每块开始时按上一块结束时的能量决定是否切换声道
+    fn pick_loudest(&mut self) {
+        if self.fade > 0 {
+            return;
+        }
+        let Some((loudest, energy)) = self
+            .energy
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            return;
+        };
+        if loudest != self.current && energy > self.energy[self.current] * self.switch_ratio {
+            self.previous = self.current;
+            self.current = loudest;
+            self.fade = self.fade_len;
+        }
+    }
+}
+
+/// 处理后的信号到输出设备声道的混音矩阵。
+///
+/// 信号只写入选中的输出声道（未选择时为全部声道），其余声道静音。
+/// 设信号有 n 个声道、选中 m 个输出声道：
+/// - n == 1：单声道复制到每个选中声道；
+/// - n <= m：第 i 个信号声道写入第 i 个选中声道，多出的选中声道静音；
+/// - n > m：第 i 个信号声道折叠到第 i % m 个选中声道，按折叠数取平均，避免削波。
+pub struct ChannelMatrix {
+    inputs: usize,
+    outputs: usize,
+    /// 行主序 outputs × inputs 权重
+    weights: Vec<f32>,
+}
+
+impl ChannelMatrix {
+    pub fn new(inputs: usize, selected: &[u16], outputs: usize) -> Result<Self, AppError> {
+        let inputs = inputs.max(1);
+        let outputs = outputs.max(1);
+        let mut targets: Vec<usize> = Vec::with_capacity(outputs);
+        if selected.is_empty() {
+            targets.extend(0..outputs);
+        }
+        for &channel in selected {
+            let channel = usize::from(channel);
+            if channel >= outputs {
+                return Err(AppError::InvalidArgument(format!(
+                    "输出设备只有 {outputs} 个声道，无法映射到第 {} 声道",
+                    channel + 1
+                )));
+            }
+            if !targets.contains(&channel) {
+                targets.push(channel);
+            }
+        }
+
+        let mut weights = vec![0.0; outputs * inputs];
+        for (slot, &output) in targets.iter().enumerate() {
+            let row = &mut weights[output * inputs..(output + 1) * inputs];
+            if inputs == 1 {
+                row[0] = 1.0;
+            } else if inputs <= targets.len() {
+                if slot < inputs {
+                    row[slot] = 1.0;
+                }
+            } else {
+                let folded: Vec<usize> = (slot..inputs).step_by(targets.len()).collect();
+                for &input in &folded {
+                    row[input] = 1.0 / folded.len() as f32;
+                }
+            }
+        }
+        Ok(Self {
+            inputs,
+            outputs,
+            weights,
+        })
+    }
+
+    /// 输出设备的声道数
+    #[cfg(target_os = "windows")]
+    pub fn channels(&self) -> usize {
+        self.outputs
+    }
+
+    /// 把交错的信号 `block` 按矩阵写入交错的输出缓冲 `data`
+    pub fn write(&self, data: &mut [f32], block: &[f32]) {
+        for (frame, source) in data
+            .chunks_exact_mut(self.outputs)
+            .zip(block.chunks_exact(self.inputs))
+        {
+            for (out, weights) in frame.iter_mut().zip(self.weights.chunks_exact(self.inputs)) {
+                *out = weights.iter().zip(source).map(|(w, s)| w * s).sum();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// 生成交错数据，第 `loud` 声道为 0.5 幅度的常数，其余声道为 0.01
+    fn interleaved(channels: usize, loud: usize, frames: usize) -> Vec<f32> {
+        (0..frames * channels)
+            .map(|i| if i % channels == loud { 0.5 } else { 0.01 })
+            .collect()
+    }
+
+    #[test]
+    fn fixed_and_average_selection_should_pick_expected_samples() {
+        let data = interleaved(4, 1, 10);
+        let mut out = Vec::new();
+
+        let mut right = InputChannelSelector::new(&InputChannel::Right, 4, RATE).unwrap();
+        right.process(&data, &mut out);
+        assert!(out.iter().all(|s| *s == 0.5));
+
+        let mut third =
+            InputChannelSelector::new(&InputChannel::Channel { index: 2 }, 4, RATE).unwrap();
+        third.process(&data, &mut out);
+        assert!(out.iter().all(|s| *s == 0.01));
+
+        let mut average = InputChannelSelector::new(&InputChannel::Average, 4, RATE).unwrap();
+        average.process(&data, &mut out);
+        assert!(out.iter().all(|s| (s - 0.1325).abs() < 1e-6));
+
+        // 单声道设备选右声道退回第一声道，越界声道直接报错
+        let mono = InputChannelSelector::new(&InputChannel::Right, 1, RATE).unwrap();
+        assert_eq!(mono.active_channel(), Some(0));
+        assert!(InputChannelSelector::new(&InputChannel::Channel { index: 4 }, 4, RATE).is_err());
+    }
+
+    #[test]
+    fn auto_selection_should_follow_loudest_channel() {
+        let mut selector = InputChannelSelector::new(&InputChannel::Auto, 2, RATE).unwrap();
+        let mut out = Vec::new();
+        for _ in 0..50 {
+            selector.process(&interleaved(2, 1, 480), &mut out);
+        }
+        assert_eq!(selector.active_channel(), Some(1));
+        assert!(out.iter().all(|s| *s == 0.5));
+
+        // 声源换到另一声道后经过交叉淡化切换
+        for _ in 0..100 {
+            selector.process(&interleaved(2, 0, 480), &mut out);
+        }
+        assert_eq!(selector.active_channel(), Some(0));
+        assert!(out.iter().all(|s| *s == 0.5));
+    }
+
+    #[test]
+    fn mono_matrix_should_write_only_selected_channels() {
+        let map = ChannelMatrix::new(1, &[1], 2).unwrap();
+        let mut data = [1.0_f32; 6];
+        map.write(&mut data, &[0.3, 0.4, 0.5]);
+        assert_eq!(data, [0.0, 0.3, 0.0, 0.4, 0.0, 0.5]);
+
+        let all = ChannelMatrix::new(1, &[], 2).unwrap();
+        all.write(&mut data, &[0.3, 0.4, 0.5]);
+        assert_eq!(data, [0.3, 0.3, 0.4, 0.4, 0.5, 0.5]);
+
+        assert!(ChannelMatrix::new(1, &[2], 2).is_err());
+    }
+
+    #[test]
+    fn passthrough_matrix_should_up_and_downmix() {
+        let mut selector = InputChannelSelector::passthrough(2);
+        let mut block = Vec::new();
+        selector.process(&[0.2, 0.6, 0.4, 0.8], &mut block);
+        assert_eq!(selector.output_channels(), 2);
+        assert_eq!(block, [0.2, 0.6, 0.4, 0.8]);
+
+        // 立体声到单声道取平均
+        let mut mono = [0.0_f32; 2];
+        ChannelMatrix::new(2, &[], 1)
+            .unwrap()
+            .write(&mut mono, &block);
+        assert!((mono[0] - 0.4).abs() < 1e-6 && (mono[1] - 0.6).abs() < 1e-6);
+
+        // 立体声到四声道：前两声道对应，其余静音
+        let mut quad = [1.0_f32; 8];
+        ChannelMatrix::new(2, &[], 4)
+            .unwrap()
+            .write(&mut quad, &block);
+        assert_eq!(quad, [0.2, 0.6, 0.0, 0.0, 0.4, 0.8, 0.0, 0.0]);
+
+        // 四声道折叠到选中的两个声道
+        let mut folded = [1.0_f32; 4];
+        ChannelMatrix::new(4, &[3, 0], 4)
+            .unwrap()
+            .write(&mut folded, &[0.1, 0.2, 0.3, 0.4]);
+        assert!((folded[3] - 0.2).abs() < 1e-6, "{folded:?}");
+        assert!((folded[0] - 0.3).abs() < 1e-6, "{folded:?}");
+        assert_eq!(&folded[1..3], [0.0, 0.0]);
+    }
+}
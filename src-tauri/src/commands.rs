@@ -4,7 +4,11 @@ use crate::{
     app_state::AppState,
     audio,
     error::AppError,
-    types::{AppConfig, AudioRouteConfig, HotkeyConfig, RuntimeStatus, VirtualMicStatus},
+    tray,
+    types::{
+        AppConfig, AudioLevels, AudioRouteConfig, DspChainConfig, EngineConfig, HotkeyConfig,
+        RuntimeStatus, VirtualMicStatus, VoiceShapingConfig,
+    },
 };
 
 #[tauri::command]
@@ -36,9 +40,42 @@ pub fn set_hotkey(
         .inner()
         .hotkey
         .apply(&app, &config, state.inner().gate.clone())?;
+    // 切换到按键静音等模式会改变空闲时的开麦状态
+    app.emit("gate_state_changed", state.inner().gate_snapshot())
+        .map_err(|e| AppError::System(format!("发射事件失败: {e}")))?;
+    tray::refresh_tray(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_engine_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    config: EngineConfig,
+) -> Result<(), AppError> {
+    state.inner().set_engine_config(config)?;
+    app.emit("engine_state_changed", state.inner().runtime_status())
+        .map_err(|e| AppError::System(format!("发射事件失败: {e}")))?;
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_dsp_chain(
+    state: tauri::State<'_, AppState>,
+    config: DspChainConfig,
+) -> Result<(), AppError> {
+    state.inner().set_dsp_chain(config)
+}
+
+#[tauri::command]
+pub fn set_input_profile(
+    state: tauri::State<'_, AppState>,
+    device_id: String,
+    profile: VoiceShapingConfig,
+) -> Result<(), AppError> {
+    state.inner().set_input_profile(device_id, profile)
+}
+
 #[tauri::command]
 pub fn start_engine(
     app: tauri::AppHandle,
@@ -79,6 +116,11 @@ pub fn get_runtime_status(state: tauri::State<'_, AppState>) -> Result<RuntimeSt
     Ok(state.inner().runtime_status())
 }
 
+#[tauri::command]
+pub fn get_audio_levels(state: tauri::State<'_, AppState>) -> Result<AudioLevels, AppError> {
+    Ok(state.inner().audio_levels().unwrap_or_default())
+}
+
 #[tauri::command]
 pub fn get_virtual_mic_status(
     state: tauri::State<'_, AppState>,
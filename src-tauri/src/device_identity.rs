@@ -0,0 +1,401 @@
+use crate::types::{AppConfig, DeviceDirection, DeviceFingerprint, DeviceInfo, DeviceList};
+
+/// 低于该置信度的候选不视为同一设备
+pub const MIN_MATCH_CONFIDENCE: f32 = 0.6;
+/// 多个候选得分相同（如两个同型号耳机）时的置信度折扣
+const AMBIGUITY_PENALTY: f32 = 0.9;
+/// 名称完全一致的得分
+const NAME_EXACT_SCORE: f32 = 0.6;
+/// 名称仅在去掉插拔序号、忽略大小写后一致的得分
+const NAME_SIMILAR_SCORE: f32 = 0.5;
+/// 支持格式完全一致的得分，部分重叠按比例计
+const CONFIGS_SCORE: f32 = 0.4;
+
+fn direction_prefix(direction: DeviceDirection) -> &'static str {
+    match direction {
+        DeviceDirection::Input => "in",
+        DeviceDirection::Output => "out",
+    }
+}
+
+/// FNV-1a 64 位哈希；标准库哈希不保证跨版本稳定，设备 id 需要持久化
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for part in parts {
+        for byte in part.iter().chain(&[0xff]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// 指纹的稳定哈希：只取后端、名称与系统端点 ID。
+///
+/// 支持的格式会随驱动更新或独占模式设置变化，只用于 [`confidence`] 匹配，不参与 id。
+fn fingerprint_hash(fingerprint: &DeviceFingerprint) -> u64 {
+    fnv1a(&[
+        fingerprint.host.as_bytes(),
+        fingerprint.name.as_bytes(),
+        fingerprint
+            .endpoint_id
+            .as_deref()
+            .unwrap_or_default()
+            .as_bytes(),
+    ])
+}
+
+/// 为同一方向的一组设备分配稳定 id。
+///
+/// id 只由指纹决定，与枚举序号无关；指纹完全相同的设备按枚举顺序追加 `:2`、`:3` 区分。
+pub fn assign_ids(fingerprints: &[DeviceFingerprint]) -> Vec<String> {
+    let hashes: Vec<u64> = fingerprints.iter().map(fingerprint_hash).collect();
+    hashes
+        .iter()
+        .enumerate()
+        .map(|(i, hash)| {
+            let prefix = direction_prefix(fingerprints[i].direction);
+            let ordinal = hashes[..i].iter().filter(|h| *h == hash).count();
+            if ordinal == 0 {
+                format!("{prefix}:{hash:016x}")
+            } else {
+                format!("{prefix}:{hash:016x}:{}", ordinal + 1)
+            }
+        })
+        .collect()
+}
+
+/// 解析旧版 `in#序号#名称` 格式的 id，返回只含名称的指纹与旧序号
+pub fn legacy_fingerprint(device_id: &str) -> Option<(DeviceFingerprint, usize)> {
+    let mut parts = device_id.splitn(3, '#');
+    let direction = match parts.next()? {
+        "in" => DeviceDirection::Input,
+        "out" => DeviceDirection::Output,
+        _ => return None,
+    };
+    let index = parts.next()?.parse().ok()?;
+    let name = parts.next()?.to_string();
+    Some((
+        DeviceFingerprint {
+            direction,
+            name,
+            host: String::new(),
+            endpoint_id: None,
+            configs: Vec::new(),
+        },
+        index,
+    ))
+}
+
+/// 去掉 Windows 重新插拔后在括号内追加的 `2- ` 序号前缀，并忽略大小写
+fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(open) = rest.find('(') {
+        normalized.push_str(&rest[..=open]);
+        rest = &rest[open + 1..];
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits > 0 && rest[digits..].starts_with("- ") {
+            rest = &rest[digits + 2..];
+        }
+    }
+    normalized.push_str(rest);
+    normalized.to_lowercase()
+}
+
+/// 两个指纹属于同一设备的置信度（0..=1）。
+///
+/// 端点 ID 都可用时直接比较；否则名称必须一致或近似，再按支持格式的重合程度加分。
+/// 任一方缺少格式信息（如旧版 id）时格式项只计一半。
+pub fn confidence(target: &DeviceFingerprint, candidate: &DeviceFingerprint) -> f32 {
+    if target.direction != candidate.direction {
+        return 0.0;
+    }
+    if let (Some(a), Some(b)) = (&target.endpoint_id, &candidate.endpoint_id) {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    if !target.host.is_empty() && !candidate.host.is_empty() && target.host != candidate.host {
+        return 0.0;
+    }
+
+    let name = if target.name == candidate.name {
+        NAME_EXACT_SCORE
+    } else if normalize_name(&target.name) == normalize_name(&candidate.name) {
+        NAME_SIMILAR_SCORE
+    } else {
+        return 0.0;
+    };
+
+    let configs = if target.configs.is_empty() || candidate.configs.is_empty() {
+        CONFIGS_SCORE / 2.0
+    } else {
+        let shared = target
+            .configs
+            .iter()
+            .filter(|c| candidate.configs.contains(c))
+            .count();
+        let total = target.configs.len() + candidate.configs.len() - shared;
+        CONFIGS_SCORE * shared as f32 / total as f32
+    };
+    name + configs
+}
+
+pub struct DeviceMatch<'a> {
+    pub device: &'a DeviceInfo,
+    pub confidence: f32,
+}
+
+/// 在候选设备中找出与指纹最匹配的一个。
+///
+/// 多个候选同分时优先取枚举序号等于 `preferred_index` 的（旧版 id 记录的序号），
+/// 否则取第一个，并按歧义折扣降低置信度。低于 [`MIN_MATCH_CONFIDENCE`] 时视为没有匹配。
+pub fn best_match<'a>(
+    target: &DeviceFingerprint,
+    candidates: &'a [DeviceInfo],
+    preferred_index: Option<usize>,
+) -> Option<DeviceMatch<'a>> {
+    let scores: Vec<f32> = candidates
+        .iter()
+        .map(|device| confidence(target, &device.fingerprint))
+        .collect();
+    let best = scores.iter().copied().fold(0.0_f32, f32::max);
+    if best <= 0.0 {
+        return None;
+    }
+
+    let tied: Vec<usize> = (0..candidates.len())
+        .filter(|&i| (scores[i] - best).abs() < 1e-6)
+        .collect();
+    let (index, confidence) = if tied.len() == 1 {
+        (tied[0], best)
+    } else {
+        let index = preferred_index
+            .filter(|i| tied.contains(i))
+            .unwrap_or(tied[0]);
+        (index, best * AMBIGUITY_PENALTY)
+    };
+
+    (confidence >= MIN_MATCH_CONFIDENCE).then(|| DeviceMatch {
+        device: &candidates[index],
+        confidence,
+    })
+}
+
+/// 一次设备 id 重新绑定
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rebind {
+    pub old_id: String,
+    pub new_id: String,
+    pub confidence: f32,
+}
+
+/// 校正配置中引用的设备 id（含备用设备列表）。
+///
+/// 当前存在的设备刷新其指纹记录；已失效的 id（包括旧版 `in#序号#名称` 格式）
+/// 按记录的指纹重新匹配，匹配成功后替换 id，并把音色设置迁移到新 id 下。
+/// 匹配不到的 id 保持不变。
+pub fn rebind_config(cfg: &mut AppConfig, devices: &DeviceList) -> Vec<Rebind> {
+    let AppConfig {
+        route,
+        input_profiles,
+        device_fingerprints,
+        ..
+    } = cfg;
+    let mut slots = vec![
+        (&mut route.input_device_id, &devices.inputs),
+        (&mut route.bridge_output_device_id, &devices.outputs),
+        (&mut route.reference_output_device_id, &devices.outputs),
+    ];
+    slots.extend(
+        route
+            .input_fallback_ids
+            .iter_mut()
+            .map(|id| (id, &devices.inputs)),
+    );
+    slots.extend(
+        route
+            .output_fallback_ids
+            .iter_mut()
+            .map(|id| (id, &devices.outputs)),
+    );
+
+    let mut rebinds = Vec::new();
+    for (slot, candidates) in slots {
+        if slot.is_empty() {
+            continue;
+        }
+        if let Some(device) = candidates.iter().find(|d| d.id == *slot) {
+            device_fingerprints.insert(device.id.clone(), device.fingerprint.clone());
+            continue;
+        }
+
+        let (target, preferred_index) = match device_fingerprints.get(slot.as_str()) {
+            Some(fingerprint) => (fingerprint.clone(), None),
+            None => match legacy_fingerprint(slot) {
+                Some((fingerprint, index)) => (fingerprint, Some(index)),
+                None => continue,
+            },
+        };
+        let Some(found) = best_match(&target, candidates, preferred_index) else {
+            continue;
+        };
+
+        let old_id = std::mem::replace(slot, found.device.id.clone());
+        device_fingerprints.remove(&old_id);
+        device_fingerprints.insert(found.device.id.clone(), found.device.fingerprint.clone());
+        if let Some(profile) = input_profiles.remove(&old_id) {
+            input_profiles
+                .entry(found.device.id.clone())
+                .or_insert(profile);
+        }
+        rebinds.push(Rebind {
+            old_id,
+            new_id: found.device.id.clone(),
+            confidence: found.confidence,
+        });
+    }
+
+    // 其余以旧版 id 保存的音色设置也迁移到新 id 下
+    let legacy: Vec<String> = input_profiles
+        .keys()
+        .filter(|id| legacy_fingerprint(id).is_some())
+        .cloned()
+        .collect();
+    for old_id in legacy {
+        let Some((target, index)) = legacy_fingerprint(&old_id) else {
+            continue;
+        };
+        let Some(found) = best_match(&target, &devices.inputs, Some(index)) else {
+            continue;
+        };
+        if let Some(profile) = input_profiles.remove(&old_id) {
+            input_profiles
+                .entry(found.device.id.clone())
+                .or_insert(profile);
+        }
+    }
+    rebinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ConfigSignature, VoiceShapingConfig};
+
+    fn fingerprint(name: &str, channels: u16) -> DeviceFingerprint {
+        DeviceFingerprint {
+            direction: DeviceDirection::Input,
+            name: name.to_string(),
+            host: "WASAPI".to_string(),
+            endpoint_id: None,
+            configs: vec![ConfigSignature {
+                channels,
+                min_sample_rate: 44_100,
+                max_sample_rate: 48_000,
+                sample_format: "f32".to_string(),
+            }],
+        }
+    }
+
+    fn inputs(fingerprints: Vec<DeviceFingerprint>) -> DeviceList {
+        let ids = assign_ids(&fingerprints);
+        DeviceList {
+            inputs: fingerprints
+                .into_iter()
+                .zip(ids)
+                .map(|(fingerprint, id)| DeviceInfo {
+                    id,
+                    name: fingerprint.name.clone(),
+                    direction: DeviceDirection::Input,
+                    is_default: false,
+                    is_virtual_candidate: false,
+                    channels: fingerprint.configs[0].channels,
+                    fingerprint,
+                })
+                .collect(),
+            outputs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn ids_should_ignore_index_and_separate_identical_devices() {
+        let usb = fingerprint("麦克风 (USB Audio)", 1);
+        let array = fingerprint("麦克风阵列 (Realtek)", 2);
+        let first = assign_ids(&[usb.clone(), array.clone()]);
+        let reordered = assign_ids(&[array.clone(), usb.clone()]);
+        assert_eq!(first[0], reordered[1]);
+        assert_eq!(first[1], reordered[0]);
+        assert!(first[0].starts_with("in:"));
+
+        let twins = assign_ids(&[usb.clone(), usb.clone()]);
+        assert_eq!(twins[0], first[0]);
+        assert_eq!(twins[1], format!("{}:2", first[0]));
+        // 支持格式变化（如驱动更新）不改变 id
+        let updated = assign_ids(&[fingerprint("麦克风 (USB Audio)", 2)]);
+        assert_eq!(updated[0], first[0]);
+
+        // 端点 ID 可用时同型号设备也能区分
+        let with_endpoint = |id: &str| DeviceFingerprint {
+            endpoint_id: Some(id.to_string()),
+            ..usb.clone()
+        };
+        let endpoints = assign_ids(&[with_endpoint("{a}"), with_endpoint("{b}")]);
+        assert_ne!(endpoints[0], endpoints[1]);
+        assert_eq!(
+            confidence(&with_endpoint("{a}"), &with_endpoint("{b}")),
+            0.0
+        );
+    }
+
+    #[test]
+    fn confidence_should_rank_name_and_config_similarity() {
+        let usb = fingerprint("麦克风 (USB Audio)", 1);
+        assert_eq!(confidence(&usb, &usb), 1.0);
+        // 重新插拔后名称带上序号前缀仍视为同一设备
+        let replugged = fingerprint("麦克风 (2- USB Audio)", 1);
+        assert!((confidence(&usb, &replugged) - 0.9).abs() < 1e-6);
+        // 名称相同但格式全变（如驱动更新后）只剩名称得分，刚好达到门限
+        let updated = confidence(&usb, &fingerprint("麦克风 (USB Audio)", 2));
+        assert!((updated - MIN_MATCH_CONFIDENCE).abs() < 1e-6);
+        assert_eq!(confidence(&usb, &fingerprint("耳机 (USB Audio)", 1)), 0.0);
+    }
+
+    #[test]
+    fn rebind_should_migrate_legacy_and_stale_ids() {
+        let devices = inputs(vec![
+            fingerprint("耳机 (USB Audio)", 1),
+            fingerprint("耳机 (USB Audio)", 1),
+            fingerprint("麦克风阵列 (Realtek)", 2),
+        ]);
+
+        // 旧版 id：同名设备有两个时按旧序号选择
+        let mut cfg = AppConfig::default();
+        cfg.route.input_device_id = "in#1#耳机 (USB Audio)".to_string();
+        cfg.input_profiles.insert(
+            cfg.route.input_device_id.clone(),
+            VoiceShapingConfig {
+                input_gain_db: 6.0,
+                ..VoiceShapingConfig::default()
+            },
+        );
+        let rebinds = rebind_config(&mut cfg, &devices);
+        assert_eq!(rebinds.len(), 1);
+        assert_eq!(cfg.route.input_device_id, devices.inputs[1].id);
+        assert!((rebinds[0].confidence - 0.72).abs() < 1e-6);
+        assert_eq!(cfg.input_profiles[&devices.inputs[1].id].input_gain_db, 6.0);
+        assert!(cfg.device_fingerprints.contains_key(&devices.inputs[1].id));
+
+        // 记录过指纹的设备在 id 失效后（插拔改名）按指纹找回
+        let replugged = inputs(vec![fingerprint("麦克风阵列 (2- Realtek)", 2)]);
+        cfg.route.input_device_id = devices.inputs[2].id.clone();
+        rebind_config(&mut cfg, &devices);
+        rebind_config(&mut cfg, &replugged);
+        assert_eq!(cfg.route.input_device_id, replugged.inputs[0].id);
+
+        // 找不到匹配时保持原 id
+        let mut missing = AppConfig::default();
+        missing.route.input_device_id = "in#0#不存在的设备".to_string();
+        assert!(rebind_config(&mut missing, &devices).is_empty());
+        assert_eq!(missing.route.input_device_id, "in#0#不存在的设备");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::types::{DeviceInfo, DeviceList, DevicesChanged};
+
+/// 设备概况：各方向的设备名称与系统默认设备名称，读取代价远低于完整枚举
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceSummary {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub default_input: String,
+    pub default_output: String,
+}
+
+/// 设备热插拔监视：对比相邻两次枚举结果，得出新增与移除的设备以及系统默认设备的变化。
+///
+/// 平时只轮询设备概况，概况变化时才需要完整枚举（含指纹与支持的格式）。
+#[derive(Default)]
+pub struct DeviceWatcher {
+    summary: Option<DeviceSummary>,
+    known: Option<DeviceList>,
+}
+
+fn default_ids(devices: &DeviceList) -> Vec<&str> {
+    devices
+        .inputs
+        .iter()
+        .chain(&devices.outputs)
+        .filter(|device| device.is_default)
+        .map(|device| device.id.as_str())
+        .collect()
+}
+
+fn missing_from(devices: &[DeviceInfo], other: &[DeviceInfo]) -> Vec<DeviceInfo> {
+    devices
+        .iter()
+        .filter(|device| !other.iter().any(|item| item.id == device.id))
+        .cloned()
+        .collect()
+}
+
+impl DeviceWatcher {
+    /// 设备概况与上次完整枚举时不同（或尚未枚举过）时返回 `true`，调用方应重新完整枚举
+    pub fn summary_changed(&self, summary: &DeviceSummary) -> bool {
+        self.summary.as_ref() != Some(summary)
+    }
+
+    /// 记录最新的设备列表及枚举时的概况；与上次相比有设备增减或默认设备变化时返回变化内容，
+    /// 首次调用只记录不报告
+    pub fn update(
+        &mut self,
+        summary: DeviceSummary,
+        devices: &DeviceList,
+    ) -> Option<DevicesChanged> {
+        self.summary = Some(summary);
+        let previous = self.known.replace(devices.clone())?;
+        let mut added = missing_from(&devices.inputs, &previous.inputs);
+        added.extend(missing_from(&devices.outputs, &previous.outputs));
+        let mut removed = missing_from(&previous.inputs, &devices.inputs);
+        removed.extend(missing_from(&previous.outputs, &devices.outputs));
+        if added.is_empty() && removed.is_empty() && default_ids(devices) == default_ids(&previous)
+        {
+            return None;
+        }
+        Some(DevicesChanged {
+            added,
+            removed,
+            devices: devices.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DeviceDirection, DeviceFingerprint};
+
+    fn device(id: &str, direction: DeviceDirection) -> DeviceInfo {
+        DeviceInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            direction,
+            is_default: false,
+            is_virtual_candidate: false,
+            channels: 2,
+            fingerprint: DeviceFingerprint {
+                direction,
+                name: id.to_string(),
+                host: "WASAPI".to_string(),
+                endpoint_id: None,
+                configs: Vec::new(),
+            },
+        }
+    }
+
+    fn list(inputs: &[&str], outputs: &[&str]) -> DeviceList {
+        DeviceList {
+            inputs: inputs
+                .iter()
+                .map(|id| device(id, DeviceDirection::Input))
+                .collect(),
+            outputs: outputs
+                .iter()
+                .map(|id| device(id, DeviceDirection::Output))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn watcher_should_report_added_and_removed_devices() {
+        let mut watcher = DeviceWatcher::default();
+        assert!(watcher
+            .update(DeviceSummary::default(), &list(&["mic"], &["cable"]))
+            .is_none());
+        assert!(watcher
+            .update(DeviceSummary::default(), &list(&["mic"], &["cable"]))
+            .is_none());
+
+        // 拔出麦克风、插入耳机（输入输出各一个端点）
+        let change = watcher
+            .update(
+                DeviceSummary::default(),
+                &list(&["headset"], &["cable", "headset-out"]),
+            )
+            .unwrap();
+        let ids = |devices: &[DeviceInfo]| -> Vec<String> {
+            devices.iter().map(|device| device.id.clone()).collect()
+        };
+        assert_eq!(ids(&change.added), ["headset", "headset-out"]);
+        assert_eq!(ids(&change.removed), ["mic"]);
+        assert_eq!(change.devices.outputs.len(), 2);
+
+        assert!(watcher
+            .update(
+                DeviceSummary::default(),
+                &list(&["headset"], &["cable", "headset-out"])
+            )
+            .is_none());
+
+        // 仅系统默认设备变化也要通知
+        let mut switched = list(&["headset"], &["cable", "headset-out"]);
+        switched.outputs[1].is_default = true;
+        let change = watcher.update(DeviceSummary::default(), &switched).unwrap();
+        assert!(change.added.is_empty() && change.removed.is_empty());
+    }
+
+    #[test]
+    fn summary_should_request_enumeration_only_on_change() {
+        let mut watcher = DeviceWatcher::default();
+        let summary = DeviceSummary {
+            inputs: vec!["mic".to_string()],
+            outputs: vec!["cable".to_string()],
+            default_input: "mic".to_string(),
+            default_output: "cable".to_string(),
+        };
+        assert!(watcher.summary_changed(&summary));
+        // 枚举成功后才记下概况，枚举失败时下次仍会重试
+        watcher.update(summary.clone(), &list(&["mic"], &["cable"]));
+        assert!(!watcher.summary_changed(&summary));
+
+        let switched = DeviceSummary {
+            default_output: String::new(),
+            ..summary
+        };
+        assert!(watcher.summary_changed(&switched));
+    }
+}
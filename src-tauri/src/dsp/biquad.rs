@@ -0,0 +1,124 @@
+use crate::types::{EqBand, EqBandKind};
+
+/// 滤波频率下限（Hz）
+const MIN_FREQUENCY_HZ: f32 = 10.0;
+/// 滤波频率上限占采样率的比例，留出余量避免接近奈奎斯特频率时失稳
+const MAX_FREQUENCY_RATIO: f32 = 0.45;
+/// 二阶巴特沃斯高通的 Q 值
+const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// 归一化（a0 = 1）后的二阶节系数，按 RBJ Audio EQ Cookbook 计算。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiquadCoeffs {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl BiquadCoeffs {
+    pub const IDENTITY: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    pub fn high_pass(frequency_hz: f32, sample_rate: u32) -> Self {
+        let (cos, alpha) = prewarp(frequency_hz, BUTTERWORTH_Q, sample_rate);
+        Self::normalized(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    pub fn eq_band(band: &EqBand, sample_rate: u32) -> Self {
+        let (cos, alpha) = prewarp(band.frequency_hz, band.q.clamp(0.1, 10.0), sample_rate);
+        let a = 10.0_f32.powf(band.gain_db.clamp(-18.0, 18.0) / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+        match band.kind {
+            EqBandKind::Peaking => Self::normalized(
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            EqBandKind::LowShelf => Self::normalized(
+                a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                (a + 1.0) + (a - 1.0) * cos + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - shelf,
+            ),
+            EqBandKind::HighShelf => Self::normalized(
+                a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                (a + 1.0) - (a - 1.0) * cos + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - shelf,
+            ),
+        }
+    }
+
+    fn normalized(b0: f32, b1: f32, b2: f32, a0: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+/// 按采样率限制频率范围后返回 (cos ω0, α)
+fn prewarp(frequency_hz: f32, q: f32, sample_rate: u32) -> (f32, f32) {
+    let rate = sample_rate.max(1) as f32;
+    let frequency = frequency_hz.clamp(MIN_FREQUENCY_HZ, rate * MAX_FREQUENCY_RATIO);
+    let omega = 2.0 * std::f32::consts::PI * frequency / rate;
+    (omega.cos(), omega.sin() / (2.0 * q))
+}
+
+/// 转置直接 II 型二阶节
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    coeffs: BiquadCoeffs,
+    z1: f32,
+    z2: f32,
+}
+
+impl Default for Biquad {
+    fn default() -> Self {
+        Self {
+            coeffs: BiquadCoeffs::IDENTITY,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+}
+
+impl Biquad {
+    /// 替换系数并保留状态，参数微调时不产生爆音
+    pub fn set_coeffs(&mut self, coeffs: BiquadCoeffs) {
+        self.coeffs = coeffs;
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let c = &self.coeffs;
+        let y = c.b0 * x + self.z1;
+        self.z1 = c.b1 * x - c.a1 * y + self.z2;
+        self.z2 = c.b2 * x - c.a2 * y;
+        y
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    mem::discriminant,
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use ringbuf::{
+    traits::{Consumer, Observer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+
+use crate::{
+    dsp::{build_processor, validate_chain, AudioProcessor},
+    error::AppError,
+    types::{DspChainConfig, ProcessorConfig, ProcessorSettings},
+};
+
+/// 控制线程到音频线程的更新队列容量
+const UPDATE_QUEUE_CAPACITY: usize = 64;
+/// 回收队列容量。控制端每次 `apply` 先清空回收队列，之后最多再投递一个结构变更；
+/// 下次清空前音频线程交回的旧结构不超过队列中已有的加上这一个，因此回收队列不会溢出
+const GARBAGE_QUEUE_CAPACITY: usize = UPDATE_QUEUE_CAPACITY + 1;
+
+struct Slot {
+    id: u32,
+    bypass: bool,
+    processor: Box<dyn AudioProcessor>,
+}
+
+/// 链路结构变更：新顺序、需要新建的处理器，以及预留好容量的槽位数组
+struct Layout {
+    order: Vec<(u32, bool)>,
+    fresh: Vec<Slot>,
+    slots: Vec<Slot>,
+}
+
+enum ChainUpdate {
+    Configure {
+        id: u32,
+        bypass: bool,
+        settings: ProcessorSettings,
+    },
+    Layout(Box<Layout>),
+}
+
+/// 创建处理链，返回控制端与音频线程端；`channels` 为交错块的声道数
+pub fn processor_chain(
+    config: &DspChainConfig,
+    sample_rate: u32,
+    channels: usize,
+) -> Result<(ChainController, ProcessorChain), AppError> {
+    validate_chain(config)?;
+
+    let (updates_tx, updates_rx) = HeapRb::<ChainUpdate>::new(UPDATE_QUEUE_CAPACITY).split();
+    let (garbage_tx, garbage_rx) = HeapRb::<Box<Layout>>::new(GARBAGE_QUEUE_CAPACITY).split();
+    let latency = Arc::new(AtomicUsize::new(0));
+    let gain_reduction = Arc::new(AtomicU32::new(0));
+
+    let chain = ProcessorChain {
+        slots: config
+            .processors
+            .iter()
+            .map(|p| new_slot(p, sample_rate, channels))
+            .collect(),
+        updates: updates_rx,
+        garbage: garbage_tx,
+        latency: latency.clone(),
+        gain_reduction: gain_reduction.clone(),
+    };
+    chain.publish_latency();
+
+    let controller = ChainController {
+        sample_rate,
+        channels,
+        updates: updates_tx,
+        garbage: garbage_rx,
+        current: config.processors.clone(),
+        latency,
+        gain_reduction,
+    };
+    Ok((controller, chain))
+}
+
+fn new_slot(config: &ProcessorConfig, sample_rate: u32, channels: usize) -> Slot {
+    Slot {
+        id: config.id,
+        bypass: config.bypass,
+        processor: build_processor(&config.settings, sample_rate, channels),
+    }
+}
+
+/// 音频线程端：按顺序执行各处理器，并在每个块开始前应用排队的更新。
+pub struct ProcessorChain {
+    slots: Vec<Slot>,
+    updates: HeapCons<ChainUpdate>,
+    garbage: HeapProd<Box<Layout>>,
+    latency: Arc<AtomicUsize>,
+    gain_reduction: Arc<AtomicU32>,
+}
+
+impl ProcessorChain {
+    pub fn process(&mut self, block: &mut [f32]) {
+        while let Some(update) = self.updates.try_pop() {
+            match update {
+                ChainUpdate::Configure {
+                    id,
+                    bypass,
+                    settings,
+                } => self.configure(id, bypass, &settings),
+                ChainUpdate::Layout(layout) => self.apply_layout(layout),
+            }
+        }
+
+        let mut reduction = 0.0_f32;
+        for slot in self.slots.iter_mut().filter(|slot| !slot.bypass) {
+            slot.processor.process(block);
+            reduction += slot.processor.gain_reduction_db();
+        }
+        self.gain_reduction
+            .store(reduction.to_bits(), Ordering::Relaxed);
+    }
+
+    fn configure(&mut self, id: u32, bypass: bool, settings: &ProcessorSettings) {
+        let Some(slot) = self.slots.iter_mut().find(|slot| slot.id == id) else {
+            return;
+        };
+        if slot.bypass && !bypass {
+            // 旁路期间内部状态已过时，重新接入前清空
+            slot.processor.reset();
+        }
+        slot.bypass = bypass;
+        slot.processor.configure(settings);
+        self.publish_latency();
+    }
+
+    fn apply_layout(&mut self, mut layout: Box<Layout>) {
+        for &(id, fresh) in layout.order.iter() {
+            let source = if fresh {
+                &mut layout.fresh
+            } else {
+                &mut self.slots
+            };
+            if let Some(pos) = source.iter().position(|slot| slot.id == id) {
+                let slot = source.swap_remove(pos);
+                layout.slots.push(slot);
+            }
+        }
+        // 旧数组与被移除的处理器交回控制线程释放，避免在实时线程内析构
+        std::mem::swap(&mut self.slots, &mut layout.slots);
+        if let Err(layout) = self.garbage.try_push(layout) {
+            // 容量保证不会走到这里（见 GARBAGE_QUEUE_CAPACITY）；宁可泄漏也不在实时线程内析构
+            debug_assert!(false, "处理链回收队列溢出");
+            std::mem::forget(layout);
+        }
+        self.publish_latency();
+    }
+
+    fn publish_latency(&self) {
+        let total = self
+            .slots
+            .iter()
+            .filter(|slot| !slot.bypass)
+            .map(|slot| slot.processor.latency())
+            .sum();
+        self.latency.store(total, Ordering::Relaxed);
+    }
+}
+
+/// 控制端：比对新旧配置，把参数与结构变更无锁投递到音频线程。
+pub struct ChainController {
+    sample_rate: u32,
+    channels: usize,
+    updates: HeapProd<ChainUpdate>,
+    garbage: HeapCons<Box<Layout>>,
+    current: Vec<ProcessorConfig>,
+    latency: Arc<AtomicUsize>,
+    gain_reduction: Arc<AtomicU32>,
+}
+
+impl ChainController {
+    /// 当前生效的处理链总时延（样本数）
+    pub fn latency_samples(&self) -> usize {
+        self.latency.load(Ordering::Relaxed)
+    }
+
+    /// 最近一个块各处理器增益衰减之和（dB）
+    pub fn gain_reduction_db(&self) -> f32 {
+        f32::from_bits(self.gain_reduction.load(Ordering::Relaxed))
+    }
+
+    /// 比对并投递更新。本次的全部更新要么一起入队，要么一个都不入队，
+    /// 因此失败时音频线程仍与 `current` 描述的链路一致
+    pub fn apply(&mut self, config: &DspChainConfig) -> Result<(), AppError> {
+        validate_chain(config)?;
+        while self.garbage.try_pop().is_some() {}
+
+        let reusable = |p: &ProcessorConfig| {
+            self.current
+                .iter()
+                .any(|c| c.id == p.id && discriminant(&c.settings) == discriminant(&p.settings))
+        };
+        let order: Vec<(u32, bool)> = config
+            .processors
+            .iter()
+            .map(|p| (p.id, !reusable(p)))
+            .collect();
+        let layout_changed = order.len() != self.current.len()
+            || order
+                .iter()
+                .zip(&self.current)
+                .any(|(&(id, fresh), current)| fresh || id != current.id);
+
+        let mut updates = Vec::new();
+        if layout_changed {
+            let fresh = config
+                .processors
+                .iter()
+                .zip(&order)
+                .filter(|(_, (_, fresh))| *fresh)
+                .map(|(p, _)| new_slot(p, self.sample_rate, self.channels))
+                .collect();
+            let layout = Layout {
+                slots: Vec::with_capacity(order.len()),
+                order,
+                fresh,
+            };
+            updates.push(ChainUpdate::Layout(Box::new(layout)));
+        }
+
+        for processor in &config.processors {
+            let changed = self.current.iter().any(|c| {
+                c.id == processor.id
+                    && discriminant(&c.settings) == discriminant(&processor.settings)
+                    && c != processor
+            });
+            if changed {
+                updates.push(ChainUpdate::Configure {
+                    id: processor.id,
+                    bypass: processor.bypass,
+                    settings: processor.settings,
+                });
+            }
+        }
+
+        if updates.len() > self.updates.vacant_len() {
+            return Err(AppError::Audio(
+                "处理链更新队列已满，请稍后重试".to_string(),
+            ));
+        }
+        // 只有本端写入，检查过的空位不会减少
+        for update in updates {
+            assert!(
+                self.updates.try_push(update).is_ok(),
+                "处理链更新队列空位已检查"
+            );
+        }
+        self.current = config.processors.clone();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gain(id: u32, gain_db: f32, bypass: bool) -> ProcessorConfig {
+        ProcessorConfig {
+            id,
+            bypass,
+            settings: ProcessorSettings::Gain { gain_db },
+        }
+    }
+
+    fn run(chain: &mut ProcessorChain) -> f32 {
+        // 跑足够多的块让增益平滑收敛
+        let mut block = [0.0_f32; 480];
+        for _ in 0..20 {
+            block.fill(0.5);
+            chain.process(&mut block);
+        }
+        block[479]
+    }
+
+    #[test]
+    fn chain_should_apply_live_updates_in_order() {
+        let config = DspChainConfig {
+            processors: vec![gain(1, 6.0, false), gain(2, -6.0, true)],
+        };
+        let (mut controller, mut chain) = processor_chain(&config, 48_000, 1).unwrap();
+        assert!((run(&mut chain) - 0.5 * 1.995).abs() < 1e-3);
+
+        // 取消旁路
+        controller
+            .apply(&DspChainConfig {
+                processors: vec![gain(1, 6.0, false), gain(2, -6.0, false)],
+            })
+            .unwrap();
+        assert!((run(&mut chain) - 0.5).abs() < 1e-3);
+
+        // 重排并调整参数、移除与新增处理器
+        controller
+            .apply(&DspChainConfig {
+                processors: vec![gain(3, 12.0, false), gain(1, 0.0, false)],
+            })
+            .unwrap();
+        assert!((run(&mut chain) - 0.5 * 3.981).abs() < 1e-2);
+        assert_eq!(chain.slots.iter().map(|s| s.id).collect::<Vec<_>>(), [3, 1]);
+
+        // 旧处理器由控制端回收
+        controller.apply(&DspChainConfig::default()).unwrap();
+        assert_eq!(run(&mut chain), 0.5);
+    }
+
+    #[test]
+    fn full_queue_should_reject_the_whole_update() {
+        let config = |first: f32, second: f32| DspChainConfig {
+            processors: vec![gain(1, first, false), gain(2, second, false)],
+        };
+        let (mut controller, mut chain) = processor_chain(&config(0.0, 0.0), 48_000, 1).unwrap();
+        // 音频线程未运行时每次只改一个处理器，把队列填到只剩一个空位
+        for i in 1..UPDATE_QUEUE_CAPACITY {
+            controller.apply(&config(i as f32 * 0.1, 0.0)).unwrap();
+        }
+        assert_eq!(chain.updates.occupied_len(), UPDATE_QUEUE_CAPACITY - 1);
+
+        // 需要两个更新时整体拒绝，一个都不入队
+        assert!(controller.apply(&config(6.0, -6.0)).is_err());
+        assert_eq!(chain.updates.occupied_len(), UPDATE_QUEUE_CAPACITY - 1);
+
+        run(&mut chain);
+        controller.apply(&config(6.0, -6.0)).unwrap();
+        assert!((run(&mut chain) - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn chain_should_report_latency_and_gain_reduction() {
+        let limiter = |bypass: bool| ProcessorConfig {
+            id: 2,
+            bypass,
+            settings: ProcessorSettings::Limiter {
+                ceiling_db: -12.0,
+                lookahead_ms: 5.0,
+                release_ms: 50.0,
+            },
+        };
+        let config = |bypass: bool| DspChainConfig {
+            processors: vec![gain(1, 0.0, false), limiter(bypass)],
+        };
+        let (mut controller, mut chain) = processor_chain(&config(false), 48_000, 1).unwrap();
+        assert_eq!(controller.latency_samples(), 240);
+        run(&mut chain);
+        // 0.5 约为 -6 dBFS，压到 -12 dBFS 需要约 6 dB 衰减
+        assert!((controller.gain_reduction_db() - 6.0).abs() < 0.1);
+
+        controller.apply(&config(true)).unwrap();
+        run(&mut chain);
+        assert_eq!(controller.latency_samples(), 0);
+        assert_eq!(controller.gain_reduction_db(), 0.0);
+    }
+
+    #[test]
+    fn duplicate_ids_should_be_rejected() {
+        let config = DspChainConfig {
+            processors: vec![gain(1, 0.0, false), gain(1, 3.0, false)],
+        };
+        assert!(processor_chain(&config, 48_000, 1).is_err());
+    }
+}
@@ -0,0 +1,481 @@
+use std::collections::VecDeque;
+
+use crate::{dsp::AudioProcessor, types::ProcessorSettings};
+
+/// 自动增益的电平检测时间常数（毫秒），按音节尺度取值
+const AGC_DETECTOR_MS: f32 = 400.0;
+/// 电平低于该值（dBFS）视为静音，冻结增益以免把底噪拉高
+const AGC_GATE_DB: f32 = -55.0;
+/// 自动增益每秒最大提升量（dB）
+const AGC_RISE_DB_PER_SEC: f32 = 6.0;
+/// 自动增益每秒最大下降量（dB），响应突然变大的音量
+const AGC_FALL_DB_PER_SEC: f32 = 30.0;
+/// 限幅器允许的最大前瞻时间（毫秒），缓冲按此预分配
+const MAX_LOOKAHEAD_MS: f32 = 20.0;
+
+/// 一阶平滑系数：`ms` 毫秒内趋近目标约 63%
+fn smoothing_coeff(ms: f32, sample_rate: u32) -> f32 {
+    1.0 - (-1000.0 / (ms.max(0.01) * sample_rate.max(1) as f32)).exp()
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.max(1e-9).log10()
+}
+
+/// 一帧内各声道的最大峰值，多声道联动检测用
+fn frame_peak(frame: &[f32]) -> f32 {
+    frame
+        .iter()
+        .fold(0.0_f32, |peak, sample| peak.max(sample.abs()))
+}
+
+/// 一帧内各声道的最大瞬时功率
+fn frame_power(frame: &[f32]) -> f32 {
+    let peak = frame_peak(frame);
+    peak * peak
+}
+
+/// 自动增益控制：以慢速 RMS 电平把语音拉向目标响度。
+///
+/// 增益变化按每秒 dB 限速，静音段冻结增益。
+pub struct AutoGainControl {
+    sample_rate: u32,
+    channels: usize,
+    target_db: f32,
+    max_gain_db: f32,
+    detector_coeff: f32,
+    mean_square: f32,
+    gain_db: f32,
+}
+
+impl AutoGainControl {
+    pub fn new(settings: &ProcessorSettings, sample_rate: u32, channels: usize) -> Self {
+        let mut processor = Self {
+            sample_rate: sample_rate.max(1),
+            channels,
+            target_db: -18.0,
+            max_gain_db: 12.0,
+            detector_coeff: smoothing_coeff(AGC_DETECTOR_MS, sample_rate),
+            mean_square: 0.0,
+            gain_db: 0.0,
+        };
+        processor.configure(settings);
+        processor
+    }
+}
+
+impl AudioProcessor for AutoGainControl {
+    fn process(&mut self, block: &mut [f32]) {
+        let rise = AGC_RISE_DB_PER_SEC / self.sample_rate as f32;
+        let fall = AGC_FALL_DB_PER_SEC / self.sample_rate as f32;
+        for frame in block.chunks_exact_mut(self.channels) {
+            let power = frame_power(frame);
+            self.mean_square += (power - self.mean_square) * self.detector_coeff;
+            let level_db = 10.0 * (self.mean_square + 1e-12).log10();
+            if level_db > AGC_GATE_DB {
+                let desired =
+                    (self.target_db - level_db).clamp(-self.max_gain_db, self.max_gain_db);
+                self.gain_db += (desired - self.gain_db).clamp(-fall, rise);
+            }
+            let gain = db_to_gain(self.gain_db);
+            frame.iter_mut().for_each(|sample| *sample *= gain);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.mean_square = 0.0;
+        self.gain_db = 0.0;
+    }
+
+    fn gain_reduction_db(&self) -> f32 {
+        (-self.gain_db).max(0.0)
+    }
+
+    fn configure(&mut self, settings: &ProcessorSettings) {
+        if let ProcessorSettings::Agc {
+            target_db,
+            max_gain_db,
+        } = settings
+        {
+            self.target_db = target_db.clamp(-40.0, 0.0);
+            self.max_gain_db = max_gain_db.clamp(0.0, 30.0);
+        }
+    }
+}
+
+/// 前馈压缩器：软拐点增益计算，增益衰减按启动/释放时间平滑。
+pub struct Compressor {
+    sample_rate: u32,
+    channels: usize,
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    makeup: f32,
+    /// 当前增益衰减（dB，≤ 0）
+    reduction_db: f32,
+}
+
+impl Compressor {
+    pub fn new(settings: &ProcessorSettings, sample_rate: u32, channels: usize) -> Self {
+        let mut processor = Self {
+            sample_rate,
+            channels,
+            threshold_db: 0.0,
+            ratio: 1.0,
+            knee_db: 0.0,
+            attack_coeff: 1.0,
+            release_coeff: 1.0,
+            makeup: 1.0,
+            reduction_db: 0.0,
+        };
+        processor.configure(settings);
+        processor
+    }
+
+    /// 静态曲线：输入电平对应的增益（dB，≤ 0）
+    fn static_gain_db(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        let slope = 1.0 / self.ratio - 1.0;
+        if 2.0 * over <= -self.knee_db {
+            0.0
+        } else if 2.0 * over.abs() <= self.knee_db {
+            slope * (over + self.knee_db / 2.0).powi(2) / (2.0 * self.knee_db)
+        } else {
+            slope * over
+        }
+    }
+}
+
+impl AudioProcessor for Compressor {
+    fn process(&mut self, block: &mut [f32]) {
+        for frame in block.chunks_exact_mut(self.channels) {
+            let target = self.static_gain_db(gain_to_db(frame_peak(frame)));
+            let coeff = if target < self.reduction_db {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            self.reduction_db += (target - self.reduction_db) * coeff;
+            let gain = db_to_gain(self.reduction_db) * self.makeup;
+            frame.iter_mut().for_each(|sample| *sample *= gain);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.reduction_db = 0.0;
+    }
+
+    fn gain_reduction_db(&self) -> f32 {
+        -self.reduction_db
+    }
+
+    fn configure(&mut self, settings: &ProcessorSettings) {
+        if let ProcessorSettings::Compressor {
+            threshold_db,
+            ratio,
+            knee_db,
+            attack_ms,
+            release_ms,
+            makeup_db,
+        } = settings
+        {
+            self.threshold_db = threshold_db.clamp(-60.0, 0.0);
+            self.ratio = ratio.clamp(1.0, 20.0);
+            self.knee_db = knee_db.clamp(0.0, 24.0);
+            self.attack_coeff = smoothing_coeff(attack_ms.clamp(0.1, 200.0), self.sample_rate);
+            self.release_coeff = smoothing_coeff(release_ms.clamp(10.0, 2000.0), self.sample_rate);
+            self.makeup = db_to_gain(makeup_db.clamp(0.0, 24.0));
+        }
+    }
+}
+
+/// 前瞻砖墙限幅器。
+///
+/// 对前瞻窗口内所需增益取滑动最小值再做等长滑动平均，
+/// 平滑后的增益在峰值到达输出端时一定不高于该峰值所需增益，保证输出不越过上限。
+/// 引入与前瞻时间相等的时延。
+pub struct Limiter {
+    sample_rate: u32,
+    channels: usize,
+    ceiling: f32,
+    release_coeff: f32,
+    /// 前瞻长度（样本数），即时延
+    lookahead: usize,
+    /// 交错存放的各声道延迟线
+    delay: Vec<f32>,
+    /// 最近 `lookahead + 1` 个样本的滑动最小值队列（序号, 所需增益）
+    minimum: VecDeque<(u64, f32)>,
+    /// 最近 `lookahead + 1` 个滑动最小值，供滑动平均使用
+    history: Vec<f32>,
+    history_sum: f64,
+    position: usize,
+    counter: u64,
+    gain: f32,
+}
+
+impl Limiter {
+    pub fn new(settings: &ProcessorSettings, sample_rate: u32, channels: usize) -> Self {
+        let capacity = (MAX_LOOKAHEAD_MS * sample_rate as f32 / 1000.0) as usize + 1;
+        let mut processor = Self {
+            sample_rate,
+            channels,
+            ceiling: 1.0,
+            release_coeff: 1.0,
+            lookahead: 0,
+            delay: vec![0.0; capacity * channels],
+            minimum: VecDeque::with_capacity(capacity + 1),
+            history: vec![1.0; capacity],
+            history_sum: 0.0,
+            position: 0,
+            counter: 0,
+            gain: 1.0,
+        };
+        processor.configure(settings);
+        processor.reset();
+        processor
+    }
+
+    fn window(&self) -> usize {
+        self.lookahead + 1
+    }
+}
+
+impl AudioProcessor for Limiter {
+    fn process(&mut self, block: &mut [f32]) {
+        let window = self.window();
+        let channels = self.channels;
+        for frame in block.chunks_exact_mut(channels) {
+            let required = (self.ceiling / frame_peak(frame).max(1e-9)).min(1.0);
+            while self.minimum.back().is_some_and(|&(_, g)| g >= required) {
+                self.minimum.pop_back();
+            }
+            self.minimum.push_back((self.counter, required));
+            while self
+                .minimum
+                .front()
+                .is_some_and(|&(n, _)| n + window as u64 <= self.counter)
+            {
+                self.minimum.pop_front();
+            }
+            let floor = self.minimum.front().map_or(1.0, |&(_, g)| g);
+
+            let slot = self.position;
+            self.history_sum += (floor - self.history[slot]) as f64;
+            self.history[slot] = floor;
+            let smoothed = (self.history_sum / window as f64) as f32;
+
+            self.gain = if smoothed < self.gain {
+                smoothed
+            } else {
+                self.gain + (smoothed - self.gain) * self.release_coeff
+            };
+
+            // 环形缓冲长度为 `lookahead + 1`，下一格正好是 `lookahead` 帧之前写入的
+            let write = slot * channels;
+            let read = (slot + 1) % window * channels;
+            self.delay[write..write + channels].copy_from_slice(frame);
+            for (c, sample) in frame.iter_mut().enumerate() {
+                *sample = self.delay[read + c] * self.gain;
+                // 增益包络本身保证不越限，只允许浮点舍入误差
+                debug_assert!(
+                    sample.abs() <= self.ceiling * (1.0 + 1e-5),
+                    "限幅器输出越限: {sample}"
+                );
+            }
+
+            self.position = (self.position + 1) % window;
+            if self.position == 0 {
+                // 每绕一圈按窗口重新求和，避免增量累加的舍入误差逐渐漂移
+                self.history_sum = self.history[..window].iter().map(|&g| g as f64).sum();
+            }
+            self.counter += 1;
+        }
+    }
+
+    fn reset(&mut self) {
+        let window = self.window();
+        self.delay.fill(0.0);
+        self.history.fill(1.0);
+        self.history_sum = window as f64;
+        self.minimum.clear();
+        self.position = 0;
+        self.gain = 1.0;
+    }
+
+    fn latency(&self) -> usize {
+        self.lookahead
+    }
+
+    fn gain_reduction_db(&self) -> f32 {
+        -gain_to_db(self.gain)
+    }
+
+    fn configure(&mut self, settings: &ProcessorSettings) {
+        if let ProcessorSettings::Limiter {
+            ceiling_db,
+            lookahead_ms,
+            release_ms,
+        } = settings
+        {
+            self.ceiling = db_to_gain(ceiling_db.clamp(-24.0, 0.0));
+            self.release_coeff = smoothing_coeff(release_ms.clamp(1.0, 1000.0), self.sample_rate);
+            let lookahead = (lookahead_ms.clamp(0.0, MAX_LOOKAHEAD_MS) * self.sample_rate as f32
+                / 1000.0) as usize;
+            if lookahead != self.lookahead {
+                // 前瞻长度改变后旧缓冲内容失效，清空重来
+                self.lookahead = lookahead;
+                self.reset();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    fn sine(seconds: f32, amplitude: f32) -> Vec<f32> {
+        (0..(seconds * RATE as f32) as usize)
+            .map(|i| {
+                (2.0 * std::f32::consts::PI * 220.0 * i as f32 / RATE as f32).sin() * amplitude
+            })
+            .collect()
+    }
+
+    fn run(processor: &mut dyn AudioProcessor, input: &[f32]) -> Vec<f32> {
+        let mut output = input.to_vec();
+        for block in output.chunks_mut(480) {
+            processor.process(block);
+        }
+        output
+    }
+
+    fn rms_db(signal: &[f32]) -> f32 {
+        let power = signal.iter().map(|s| s * s).sum::<f32>() / signal.len() as f32;
+        10.0 * power.log10()
+    }
+
+    fn peak_db(signal: &[f32]) -> f32 {
+        gain_to_db(signal.iter().fold(0.0_f32, |m, s| m.max(s.abs())))
+    }
+
+    #[test]
+    fn agc_should_pull_quiet_and_loud_voices_to_target() {
+        let settings = ProcessorSettings::Agc {
+            target_db: -20.0,
+            max_gain_db: 15.0,
+        };
+        // -33dBFS 与 -8dBFS 的两路输入都应收敛到目标附近
+        for amplitude in [0.03, 0.56] {
+            let mut agc = AutoGainControl::new(&settings, RATE, 1);
+            let output = run(&mut agc, &sine(6.0, amplitude));
+            let level = rms_db(&output[RATE as usize * 5..]);
+            assert!(
+                (level + 20.0).abs() < 1.5,
+                "amplitude {amplitude}: {level}dB"
+            );
+        }
+
+        let mut agc = AutoGainControl::new(&settings, RATE, 1);
+        run(&mut agc, &sine(3.0, 0.56));
+        assert!(agc.gain_reduction_db() > 8.0);
+
+        // 静音段不应被放大
+        let mut agc = AutoGainControl::new(&settings, RATE, 1);
+        let output = run(&mut agc, &sine(3.0, 0.001));
+        assert!(rms_db(&output) < -55.0);
+    }
+
+    #[test]
+    fn compressor_should_follow_static_curve() {
+        let settings = ProcessorSettings::Compressor {
+            threshold_db: -20.0,
+            ratio: 4.0,
+            knee_db: 0.0,
+            attack_ms: 1.0,
+            release_ms: 200.0,
+            makeup_db: 0.0,
+        };
+        let mut compressor = Compressor::new(&settings, RATE, 1);
+        // 峰值 -6dBFS，超出门限 14dB，按 4:1 压缩后峰值约 -16.5dBFS
+        let output = run(&mut compressor, &sine(1.0, 0.5));
+        let peak = peak_db(&output[RATE as usize / 2..]);
+        assert!((peak + 16.5).abs() < 1.0, "peak = {peak}");
+        assert!(compressor.gain_reduction_db() > 8.0);
+
+        // 门限以下保持原样
+        let mut compressor = Compressor::new(&settings, RATE, 1);
+        let quiet = sine(0.5, 0.05);
+        let output = run(&mut compressor, &quiet);
+        assert!(output.iter().zip(&quiet).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[test]
+    fn limiter_should_never_exceed_ceiling() {
+        let settings = ProcessorSettings::Limiter {
+            ceiling_db: -1.0,
+            lookahead_ms: 5.0,
+            release_ms: 50.0,
+        };
+        let ceiling = db_to_gain(-1.0);
+        let mut limiter = Limiter::new(&settings, RATE, 1);
+
+        // 安静段、突发的满幅尖峰与持续过载混合
+        let mut input = sine(0.5, 0.2);
+        input.extend(sine(0.5, 0.2).iter().enumerate().map(|(i, s)| {
+            if i % 4800 < 24 {
+                if i % 2 == 0 {
+                    3.0
+                } else {
+                    -3.0
+                }
+            } else {
+                *s
+            }
+        }));
+        input.extend(sine(0.5, 2.0));
+        let output = run(&mut limiter, &input);
+
+        // 输出端没有硬削波，峰值完全由前瞻增益包络压住
+        let peak = output.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        assert!(peak <= ceiling * (1.0 + 1e-5), "peak = {peak}");
+        assert!(peak > ceiling * 0.99, "peak = {peak}");
+        assert!(limiter.gain_reduction_db() > 5.0);
+
+        // 未过载段仅延迟、不改变
+        let latency = limiter.latency();
+        assert_eq!(latency, 240);
+        assert!(output[latency..RATE as usize / 4]
+            .iter()
+            .zip(&input)
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[test]
+    fn stereo_limiter_should_link_channels() {
+        let settings = ProcessorSettings::Limiter {
+            ceiling_db: -1.0,
+            lookahead_ms: 5.0,
+            release_ms: 50.0,
+        };
+        let ceiling = db_to_gain(-1.0);
+        let mut limiter = Limiter::new(&settings, RATE, 2);
+        // 左声道过载、右声道安静，两声道应承受相同的增益衰减
+        let input: Vec<f32> = sine(0.5, 2.0).iter().flat_map(|s| [*s, *s * 0.1]).collect();
+        let output = run(&mut limiter, &input);
+
+        let tail = &output[RATE as usize / 2..];
+        assert!(tail.iter().all(|s| s.abs() <= ceiling + 1e-5));
+        for frame in tail.chunks_exact(2).filter(|f| f[0].abs() > 0.1) {
+            assert!((frame[1] / frame[0] - 0.1).abs() < 1e-3, "{frame:?}");
+        }
+    }
+}
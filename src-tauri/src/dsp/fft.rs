@@ -0,0 +1,95 @@
+/// 原地基 2 复数 FFT，旋转因子在创建时预先计算，变换过程不分配内存。
+pub struct Fft {
+    twiddles: Vec<(f32, f32)>,
+}
+
+impl Fft {
+    /// `len` 须为 2 的幂
+    pub fn new(len: usize) -> Self {
+        let twiddles = (0..len / 2)
+            .map(|k| {
+                let angle = -2.0 * std::f32::consts::PI * k as f32 / len as f32;
+                (angle.cos(), angle.sin())
+            })
+            .collect();
+        Self { twiddles }
+    }
+
+    pub fn forward(&self, re: &mut [f32], im: &mut [f32]) {
+        self.transform(re, im);
+    }
+
+    /// 逆变换，结果已按长度归一化
+    pub fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        // 共轭 → 正变换 → 共轭
+        im.iter_mut().for_each(|v| *v = -*v);
+        self.transform(re, im);
+        let scale = 1.0 / re.len() as f32;
+        re.iter_mut().for_each(|v| *v *= scale);
+        im.iter_mut().for_each(|v| *v *= -scale);
+    }
+
+    fn transform(&self, re: &mut [f32], im: &mut [f32]) {
+        let n = re.len();
+        debug_assert_eq!(n, self.twiddles.len() * 2);
+
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut size = 2;
+        while size <= n {
+            let step = n / size;
+            for start in (0..n).step_by(size) {
+                for k in 0..size / 2 {
+                    let (wr, wi) = self.twiddles[k * step];
+                    let a = start + k;
+                    let b = a + size / 2;
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            size <<= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_should_locate_pure_tone_and_round_trip() {
+        let n = 1024;
+        let fft = Fft::new(n);
+        let original: Vec<f32> = (0..n)
+            .map(|i| (2.0 * std::f32::consts::PI * 32.0 * i as f32 / n as f32).cos())
+            .collect();
+        let mut re = original.clone();
+        let mut im = vec![0.0; n];
+        fft.forward(&mut re, &mut im);
+
+        let peak = (0..n / 2)
+            .max_by(|a, b| re[*a].hypot(im[*a]).total_cmp(&re[*b].hypot(im[*b])))
+            .unwrap();
+        assert_eq!(peak, 32);
+
+        fft.inverse(&mut re, &mut im);
+        assert!(re.iter().zip(&original).all(|(a, b)| (a - b).abs() < 1e-4));
+        assert!(im.iter().all(|v| v.abs() < 1e-4));
+    }
+}
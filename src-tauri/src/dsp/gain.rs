@@ -0,0 +1,47 @@
+use crate::{dsp::AudioProcessor, types::ProcessorSettings};
+
+const MIN_GAIN_DB: f32 = -24.0;
+const MAX_GAIN_DB: f32 = 24.0;
+/// 增益变化的平滑时间常数（毫秒），避免调节时产生拉链噪声
+const SMOOTHING_MS: f32 = 10.0;
+
+/// 固定增益，参数变化时逐样本平滑过渡。
+pub struct GainProcessor {
+    channels: usize,
+    target: f32,
+    current: f32,
+    coeff: f32,
+}
+
+impl GainProcessor {
+    pub fn new(settings: &ProcessorSettings, sample_rate: u32, channels: usize) -> Self {
+        let mut processor = Self {
+            channels,
+            target: 1.0,
+            current: 1.0,
+            coeff: 1.0 - (-1000.0 / (SMOOTHING_MS * sample_rate.max(1) as f32)).exp(),
+        };
+        processor.configure(settings);
+        processor.current = processor.target;
+        processor
+    }
+}
+
+impl AudioProcessor for GainProcessor {
+    fn process(&mut self, block: &mut [f32]) {
+        for frame in block.chunks_exact_mut(self.channels) {
+            self.current += (self.target - self.current) * self.coeff;
+            frame.iter_mut().for_each(|sample| *sample *= self.current);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.current = self.target;
+    }
+
+    fn configure(&mut self, settings: &ProcessorSettings) {
+        if let ProcessorSettings::Gain { gain_db } = settings {
+            self.target = 10.0_f32.powf(gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB) / 20.0);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(any(target_os = "windows", test))]
+mod biquad;
+#[cfg(any(target_os = "windows", test))]
+pub mod chain;
+#[cfg(any(target_os = "windows", test))]
+mod dynamics;
+#[cfg(any(target_os = "windows", test))]
+pub mod fft;
+#[cfg(any(target_os = "windows", test))]
+mod gain;
+#[cfg(any(target_os = "windows", test))]
+mod noise_suppressor;
+#[cfg(any(target_os = "windows", test))]
+pub mod shaping;
+
+#[cfg(any(target_os = "windows", test))]
+use crate::types::ProcessorSettings;
+use crate::{
+    error::AppError,
+    types::{DspChainConfig, VoiceShapingConfig},
+};
+
+/// 参数均衡最多允许的频段数
+pub const MAX_EQ_BANDS: usize = 8;
+
+/// 处理链中的单个处理器，在输入回调内对交错多声道块原地处理。
+///
+/// 声道数在创建时确定；多声道时动态处理器按全部声道联动检测，保持声像不漂移。
+/// 实现必须在 `process`/`configure` 中避免加锁与分配内存。
+#[cfg(any(target_os = "windows", test))]
+pub trait AudioProcessor: Send {
+    /// 原地处理一个交错块，长度为声道数的整数倍
+    fn process(&mut self, block: &mut [f32]);
+
+    /// 清空内部状态，旁路结束后重新接入时调用
+    fn reset(&mut self);
+
+    /// 处理器引入的时延（样本数）
+    fn latency(&self) -> usize {
+        0
+    }
+
+    /// 当前增益衰减量（dB，≥ 0），供状态面板展示
+    fn gain_reduction_db(&self) -> f32 {
+        0.0
+    }
+
+    /// 应用新参数；`settings` 保证与创建时的类型一致
+    fn configure(&mut self, settings: &ProcessorSettings);
+}
+
+/// 按参数创建处理器
+#[cfg(any(target_os = "windows", test))]
+pub fn build_processor(
+    settings: &ProcessorSettings,
+    sample_rate: u32,
+    channels: usize,
+) -> Box<dyn AudioProcessor> {
+    let channels = channels.max(1);
+    match settings {
+        ProcessorSettings::Gain { .. } => {
+            Box::new(gain::GainProcessor::new(settings, sample_rate, channels))
+        }
+        ProcessorSettings::NoiseSuppression { .. } => Box::new(
+            noise_suppressor::NoiseSuppressor::new(settings, sample_rate, channels),
+        ),
+        ProcessorSettings::Agc { .. } => Box::new(dynamics::AutoGainControl::new(
+            settings,
+            sample_rate,
+            channels,
+        )),
+        ProcessorSettings::Compressor { .. } => {
+            Box::new(dynamics::Compressor::new(settings, sample_rate, channels))
+        }
+        ProcessorSettings::Limiter { .. } => {
+            Box::new(dynamics::Limiter::new(settings, sample_rate, channels))
+        }
+    }
+}
+
+/// 校验处理链配置：处理器 id 不可重复
+pub fn validate_chain(config: &DspChainConfig) -> Result<(), AppError> {
+    for (i, processor) in config.processors.iter().enumerate() {
+        if config.processors[..i].iter().any(|p| p.id == processor.id) {
+            return Err(AppError::InvalidArgument(format!(
+                "处理器 id 重复: {}",
+                processor.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 校验音色设置：频段数量受限，数值须为有限值
+pub fn validate_shaping(config: &VoiceShapingConfig) -> Result<(), AppError> {
+    if config.eq.len() > MAX_EQ_BANDS {
+        return Err(AppError::InvalidArgument(format!(
+            "均衡频段最多 {MAX_EQ_BANDS} 个"
+        )));
+    }
+    let finite = config.input_gain_db.is_finite()
+        && config.high_pass_hz.is_finite()
+        && config.high_pass_hz >= 0.0
+        && config.eq.iter().all(|band| {
+            band.frequency_hz.is_finite()
+                && band.gain_db.is_finite()
+                && band.q.is_finite()
+                && band.q > 0.0
+        });
+    if !finite {
+        return Err(AppError::InvalidArgument("音色参数无效".to_string()));
+    }
+    Ok(())
+}
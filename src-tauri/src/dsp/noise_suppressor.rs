@@ -0,0 +1,287 @@
+use crate::{
+    dsp::{fft::Fft, AudioProcessor},
+    types::ProcessorSettings,
+};
+
+/// 分析帧目标时长（毫秒），实际帧长取不小于该值的 2 的幂
+const FRAME_MS: u32 = 10;
+/// 强度为 1 时的最大衰减（dB）
+const MAX_ATTENUATION_DB: f32 = 30.0;
+/// 判决引导先验信噪比的平滑系数
+const PRIORI_SMOOTHING: f32 = 0.98;
+/// 带噪功率谱的递归平滑系数
+const POWER_SMOOTHING: f32 = 0.7;
+/// 噪声估计每秒允许的上升量（dB），下降则立即跟随
+const NOISE_RISE_DB_PER_SEC: f32 = 4.0;
+/// 最小值跟踪会低估噪声均值，按经验系数补偿
+const NOISE_BIAS: f32 = 4.0;
+
+/// 维纳滤波降噪。
+///
+/// 50% 重叠的短时傅里叶变换，逐频点以最小值跟踪估计噪声功率，
+/// 按判决引导法估计先验信噪比求维纳增益，并按强度限制最大衰减。
+/// 引入一个分析帧长度的固定时延。多声道时各声道独立估计噪声。
+pub struct NoiseSuppressor {
+    channels: Vec<ChannelSuppressor>,
+}
+
+impl NoiseSuppressor {
+    pub fn new(settings: &ProcessorSettings, sample_rate: u32, channels: usize) -> Self {
+        let mut processor = Self {
+            channels: (0..channels.max(1))
+                .map(|_| ChannelSuppressor::new(sample_rate))
+                .collect(),
+        };
+        processor.configure(settings);
+        processor
+    }
+}
+
+impl AudioProcessor for NoiseSuppressor {
+    fn process(&mut self, block: &mut [f32]) {
+        for frame in block.chunks_exact_mut(self.channels.len()) {
+            for (sample, channel) in frame.iter_mut().zip(&mut self.channels) {
+                *sample = channel.push(*sample);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.channels.iter_mut().for_each(ChannelSuppressor::reset);
+    }
+
+    fn latency(&self) -> usize {
+        self.channels[0].input.len()
+    }
+
+    fn configure(&mut self, settings: &ProcessorSettings) {
+        if let ProcessorSettings::NoiseSuppression { strength } = settings {
+            let attenuation = strength.clamp(0.0, 1.0) * MAX_ATTENUATION_DB;
+            let gain_floor = 10.0_f32.powf(-attenuation / 20.0);
+            for channel in &mut self.channels {
+                channel.gain_floor = gain_floor;
+            }
+        }
+    }
+}
+
+/// 单个声道的分析/合成状态
+struct ChannelSuppressor {
+    fft: Fft,
+    window: Vec<f32>,
+    hop: usize,
+    /// 最近一帧输入
+    input: Vec<f32>,
+    /// 当前跳内已写入/已读出的位置
+    pos: usize,
+    overlap: Vec<f32>,
+    ready: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    power: Vec<f32>,
+    noise: Vec<f32>,
+    prev_gain: Vec<f32>,
+    prev_post: Vec<f32>,
+    noise_rise: f32,
+    gain_floor: f32,
+}
+
+impl ChannelSuppressor {
+    fn new(sample_rate: u32) -> Self {
+        let len = ((sample_rate * FRAME_MS / 1000) as usize)
+            .next_power_of_two()
+            .max(64);
+        let hop = len / 2;
+        let bins = len / 2 + 1;
+        // 周期 Hann 开方作分析/合成窗，50% 重叠时可完美重建
+        let window = (0..len)
+            .map(|i| {
+                let x = i as f32 / len as f32;
+                (0.5 - 0.5 * (2.0 * std::f32::consts::PI * x).cos()).sqrt()
+            })
+            .collect();
+        let hop_secs = hop as f32 / sample_rate.max(1) as f32;
+
+        Self {
+            fft: Fft::new(len),
+            window,
+            hop,
+            input: vec![0.0; len],
+            pos: 0,
+            overlap: vec![0.0; len],
+            ready: vec![0.0; hop],
+            re: vec![0.0; len],
+            im: vec![0.0; len],
+            power: vec![0.0; bins],
+            noise: vec![0.0; bins],
+            prev_gain: vec![1.0; bins],
+            prev_post: vec![1.0; bins],
+            noise_rise: 10.0_f32.powf(NOISE_RISE_DB_PER_SEC * hop_secs / 10.0),
+            gain_floor: 1.0,
+        }
+    }
+
+    /// 写入一个样本并取出一个帧长之前的输出样本
+    fn push(&mut self, sample: f32) -> f32 {
+        let offset = self.input.len() - self.hop;
+        self.input[offset + self.pos] = sample;
+        let output = self.ready[self.pos];
+        self.pos += 1;
+        if self.pos == self.hop {
+            self.pos = 0;
+            self.process_frame();
+            self.input.copy_within(self.hop.., 0);
+        }
+        output
+    }
+
+    fn reset(&mut self) {
+        self.input.fill(0.0);
+        self.overlap.fill(0.0);
+        self.ready.fill(0.0);
+        self.power.fill(0.0);
+        self.noise.fill(0.0);
+        self.prev_gain.fill(1.0);
+        self.prev_post.fill(1.0);
+        self.pos = 0;
+    }
+
+    fn process_frame(&mut self) {
+        let len = self.input.len();
+        for i in 0..len {
+            self.re[i] = self.input[i] * self.window[i];
+            self.im[i] = 0.0;
+        }
+        self.fft.forward(&mut self.re, &mut self.im);
+
+        for k in 0..self.power.len() {
+            let p = self.re[k] * self.re[k] + self.im[k] * self.im[k];
+            self.power[k] = POWER_SMOOTHING * self.power[k] + (1.0 - POWER_SMOOTHING) * p;
+            if self.noise[k] == 0.0 || self.power[k] < self.noise[k] {
+                self.noise[k] = self.power[k];
+            } else {
+                self.noise[k] *= self.noise_rise;
+            }
+
+            let noise = (self.noise[k] * NOISE_BIAS).max(1e-12);
+            let post = p / noise;
+            let priori = PRIORI_SMOOTHING * self.prev_gain[k].powi(2) * self.prev_post[k]
+                + (1.0 - PRIORI_SMOOTHING) * (post - 1.0).max(0.0);
+            let gain = (priori / (1.0 + priori)).max(self.gain_floor);
+            self.prev_gain[k] = gain;
+            self.prev_post[k] = post;
+
+            self.re[k] *= gain;
+            self.im[k] *= gain;
+            if k > 0 && k < len / 2 {
+                // 保持共轭对称，逆变换结果为实数
+                self.re[len - k] = self.re[k];
+                self.im[len - k] = -self.im[k];
+            }
+        }
+        self.fft.inverse(&mut self.re, &mut self.im);
+
+        for i in 0..len {
+            self.overlap[i] += self.re[i] * self.window[i];
+        }
+        self.ready.copy_from_slice(&self.overlap[..self.hop]);
+        self.overlap.copy_within(self.hop.., 0);
+        self.overlap[len - self.hop..].fill(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+    const TONE_HZ: f32 = 440.0;
+    /// 音节式的断续音：300ms 有声、300ms 静默
+    const BURST: usize = RATE as usize * 3 / 10;
+
+    fn tone_bursts(len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                if (i / BURST) % 2 == 0 {
+                    (2.0 * std::f32::consts::PI * TONE_HZ * i as f32 / RATE as f32).sin() * 0.3
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 0x1234_5678_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    fn run(strength: f32, input: &[f32]) -> (Vec<f32>, usize) {
+        let settings = ProcessorSettings::NoiseSuppression { strength };
+        let mut suppressor = NoiseSuppressor::new(&settings, RATE, 1);
+        let mut output = input.to_vec();
+        for block in output.chunks_mut(480) {
+            suppressor.process(block);
+        }
+        // 补偿处理时延，使输出与输入逐样本对齐
+        let latency = suppressor.latency();
+        (output[latency..].to_vec(), latency)
+    }
+
+    /// 在有声段（去掉边沿）统计纯音与噪声功率，返回 SNR（dB）
+    fn snr_db(signal: &[f32], clean: &[f32], skip: usize) -> f32 {
+        let margin = BURST / 10;
+        let mut tone_power = 0.0_f64;
+        let mut noise_power = 0.0_f64;
+        for (i, (s, c)) in signal.iter().zip(clean).enumerate().skip(skip) {
+            let within = i % BURST;
+            if (i / BURST) % 2 != 0 || within < margin || within > BURST - margin {
+                continue;
+            }
+            tone_power += (*c as f64).powi(2);
+            noise_power += (*s as f64 - *c as f64).powi(2);
+        }
+        (10.0 * (tone_power / noise_power).log10()) as f32
+    }
+
+    #[test]
+    fn suppressor_should_improve_snr_of_noisy_tone() {
+        let len = RATE as usize * 6;
+        let clean = tone_bursts(len);
+        let noisy: Vec<f32> = clean
+            .iter()
+            .zip(noise(len, 0.1))
+            .map(|(s, n)| s + n)
+            .collect();
+        let (output, _) = run(1.0, &noisy);
+
+        // 跳过噪声估计收敛段后比较
+        let skip = RATE as usize * 2;
+        let before = snr_db(&noisy, &clean, skip);
+        let after = snr_db(&output, &clean, skip);
+        assert!(after - before > 10.0, "before = {before}, after = {after}");
+
+        // 静默段的残余噪声应明显降低
+        let gap = BURST * 7..BURST * 8;
+        let power = |s: &[f32]| s.iter().map(|v| v * v).sum::<f32>() / s.len() as f32;
+        let reduction = 10.0 * (power(&noisy[gap.clone()]) / power(&output[gap])).log10();
+        assert!(reduction > 12.0, "reduction = {reduction}");
+    }
+
+    #[test]
+    fn zero_strength_should_only_delay_signal() {
+        let input = tone_bursts(RATE as usize);
+        let (output, _) = run(0.0, &input);
+        assert!(output
+            .iter()
+            .zip(&input)
+            .all(|(out, reference)| (out - reference).abs() < 1e-3));
+    }
+}
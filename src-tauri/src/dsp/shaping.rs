@@ -0,0 +1,322 @@
+use ringbuf::{
+    traits::{Consumer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+
+use crate::{
+    dsp::{
+        biquad::{Biquad, BiquadCoeffs},
+        validate_shaping, MAX_EQ_BANDS,
+    },
+    error::AppError,
+    types::{EqBandKind, VoiceShapingConfig},
+};
+
+/// 高通 + 各均衡频段
+const MAX_STAGES: usize = MAX_EQ_BANDS + 1;
+const MAX_INPUT_GAIN_DB: f32 = 24.0;
+/// 输入增益变化的平滑时间常数（毫秒）
+const GAIN_SMOOTHING_MS: f32 = 10.0;
+const UPDATE_QUEUE_CAPACITY: usize = 16;
+
+/// 滤波级对应的频段，参数更新时据此把滤波状态交给同一频段
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    HighPass,
+    Eq { index: usize, kind: EqBandKind },
+}
+
+/// 已按采样率算好的全部系数，定长以便无分配地投递到音频线程
+#[derive(Clone, Copy)]
+struct ShapingParams {
+    sample_rate: u32,
+    gain: f32,
+    stages: [BiquadCoeffs; MAX_STAGES],
+    ids: [Stage; MAX_STAGES],
+    count: usize,
+}
+
+impl ShapingParams {
+    fn compute(config: &VoiceShapingConfig, sample_rate: u32) -> Self {
+        let mut stages = [BiquadCoeffs::IDENTITY; MAX_STAGES];
+        let mut ids = [Stage::HighPass; MAX_STAGES];
+        let mut count = 0;
+        if config.high_pass_hz > 0.0 {
+            stages[count] = BiquadCoeffs::high_pass(config.high_pass_hz, sample_rate);
+            count += 1;
+        }
+        for (index, band) in config.eq.iter().take(MAX_EQ_BANDS).enumerate() {
+            stages[count] = BiquadCoeffs::eq_band(band, sample_rate);
+            ids[count] = Stage::Eq {
+                index,
+                kind: band.kind,
+            };
+            count += 1;
+        }
+        Self {
+            sample_rate,
+            gain: 10.0_f32.powf(
+                config
+                    .input_gain_db
+                    .clamp(-MAX_INPUT_GAIN_DB, MAX_INPUT_GAIN_DB)
+                    / 20.0,
+            ),
+            stages,
+            ids,
+            count,
+        }
+    }
+}
+
+/// 创建音色处理，返回控制端与音频线程端；`channels` 为交错块的声道数
+pub fn voice_shaper(
+    config: &VoiceShapingConfig,
+    sample_rate: u32,
+    channels: usize,
+) -> Result<(ShaperController, VoiceShaper), AppError> {
+    validate_shaping(config)?;
+    let params = ShapingParams::compute(config, sample_rate);
+    let (updates_tx, updates_rx) = HeapRb::<ShapingParams>::new(UPDATE_QUEUE_CAPACITY).split();
+
+    let mut filters = vec![[Biquad::default(); MAX_STAGES]; channels.max(1)];
+    for stages in &mut filters {
+        for (filter, coeffs) in stages.iter_mut().zip(&params.stages) {
+            filter.set_coeffs(*coeffs);
+        }
+    }
+    let shaper = VoiceShaper {
+        params,
+        filters,
+        gain: params.gain,
+        gain_coeff: 1.0 - (-1000.0 / (GAIN_SMOOTHING_MS * sample_rate.max(1) as f32)).exp(),
+        updates: updates_rx,
+    };
+    let controller = ShaperController {
+        sample_rate,
+        updates: updates_tx,
+    };
+    Ok((controller, shaper))
+}
+
+/// 音频线程端：输入增益 → 高通 → 参数均衡。各声道共用参数、各自保留滤波状态。
+pub struct VoiceShaper {
+    params: ShapingParams,
+    /// 每个声道一组滤波器
+    filters: Vec<[Biquad; MAX_STAGES]>,
+    gain: f32,
+    gain_coeff: f32,
+    updates: HeapCons<ShapingParams>,
+}
+
+impl VoiceShaper {
+    pub fn process(&mut self, block: &mut [f32]) {
+        while let Some(params) = self.updates.try_pop() {
+            self.apply(params);
+        }
+
+        let count = self.params.count;
+        for frame in block.chunks_exact_mut(self.filters.len()) {
+            self.gain += (self.params.gain - self.gain) * self.gain_coeff;
+            for (sample, stages) in frame.iter_mut().zip(&mut self.filters) {
+                let mut value = *sample * self.gain;
+                for filter in &mut stages[..count] {
+                    value = filter.process(value);
+                }
+                *sample = value;
+            }
+        }
+    }
+
+    /// 输入源交接时应用随切换携带的参数。队列里的更新都早于本次参数，直接丢弃以免覆盖
+    pub fn apply_staged(&mut self, staged: StagedShaping) {
+        while self.updates.try_pop().is_some() {}
+        self.apply(staged.0);
+    }
+
+    fn apply(&mut self, params: ShapingParams) {
+        // 滤波状态按频段交接：同一频段沿用状态，调参时不爆音；开关高通使各级错位时状态随频段移动，
+        // 新增或换了类型的频段从零状态开始。采样率变化时旧状态对应另一采样率，直接沿用可能失稳
+        let previous = &self.params;
+        let same_rate = params.sample_rate == previous.sample_rate;
+        for stages in &mut self.filters {
+            let old = *stages;
+            *stages = std::array::from_fn(|i| {
+                let mut filter = previous.ids[..previous.count]
+                    .iter()
+                    .position(|id| same_rate && i < params.count && *id == params.ids[i])
+                    .map_or_else(Biquad::default, |j| old[j]);
+                filter.set_coeffs(params.stages[i]);
+                filter
+            });
+        }
+        self.params = params;
+    }
+}
+
+/// 预先算好的参数，由持有 [`VoiceShaper`] 的线程在合适的时机通过 `apply_staged` 生效
+#[derive(Clone, Copy)]
+pub struct StagedShaping(ShapingParams);
+
+/// 控制端：按当前采样率重新计算系数后无锁投递。
+pub struct ShaperController {
+    sample_rate: u32,
+    updates: HeapProd<ShapingParams>,
+}
+
+impl ShaperController {
+    pub fn apply(&mut self, config: &VoiceShapingConfig) -> Result<(), AppError> {
+        validate_shaping(config)?;
+        self.updates
+            .try_push(ShapingParams::compute(config, self.sample_rate))
+            .map_err(|_| AppError::Audio("音色更新队列已满，请稍后重试".to_string()))
+    }
+
+    /// 只计算参数不投递，交给调用方随其他操作一起生效
+    pub fn stage(&self, config: &VoiceShapingConfig) -> Result<StagedShaping, AppError> {
+        validate_shaping(config)?;
+        Ok(StagedShaping(ShapingParams::compute(
+            config,
+            self.sample_rate,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{EqBand, EqBandKind};
+
+    /// 测量单频正弦经过处理后的增益（dB）
+    fn response_db(config: &VoiceShapingConfig, sample_rate: u32, frequency: f32) -> f32 {
+        let (_, mut shaper) = voice_shaper(config, sample_rate, 1).unwrap();
+        let mut signal: Vec<f32> = (0..sample_rate as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate as f32).sin())
+            .collect();
+        for block in signal.chunks_mut(480) {
+            shaper.process(block);
+        }
+        let tail = &signal[sample_rate as usize / 2..];
+        let rms = (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt();
+        20.0 * (rms * std::f32::consts::SQRT_2).log10()
+    }
+
+    fn voice_profile() -> VoiceShapingConfig {
+        VoiceShapingConfig {
+            input_gain_db: 6.0,
+            high_pass_hz: 100.0,
+            eq: vec![
+                EqBand {
+                    kind: EqBandKind::Peaking,
+                    frequency_hz: 3000.0,
+                    gain_db: 6.0,
+                    q: 1.0,
+                },
+                EqBand {
+                    kind: EqBandKind::HighShelf,
+                    frequency_hz: 9000.0,
+                    gain_db: -6.0,
+                    q: 0.7,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn shaper_should_apply_gain_high_pass_and_eq() {
+        let config = voice_profile();
+        // 高通：20Hz 明显衰减
+        assert!(response_db(&config, 48_000, 20.0) < 6.0 - 20.0);
+        // 1kHz 附近只有输入增益
+        assert!((response_db(&config, 48_000, 1000.0) - 6.0).abs() < 1.0);
+        // 峰值频段叠加 +6dB
+        assert!((response_db(&config, 48_000, 3000.0) - 12.0).abs() < 0.5);
+        // 高搁架在高频段衰减
+        assert!(response_db(&config, 48_000, 16_000.0) < 1.0);
+    }
+
+    #[test]
+    fn coefficients_should_follow_sample_rate_and_stay_stable() {
+        let config = voice_profile();
+        for rate in [16_000, 44_100, 48_000, 96_000] {
+            let gain = response_db(&config, rate, 3000.0);
+            assert!((gain - 12.0).abs() < 0.5, "{rate}Hz: {gain}dB");
+        }
+
+        // 8kHz 采样率下 9kHz 搁架超出奈奎斯特频率，应被限制而不是发散
+        let gain = response_db(&config, 8_000, 1000.0);
+        assert!(gain.is_finite() && gain < 12.0, "gain = {gain}");
+    }
+
+    #[test]
+    fn live_update_should_take_effect_without_restart() {
+        let (mut controller, mut shaper) =
+            voice_shaper(&VoiceShapingConfig::default(), 48_000, 1).unwrap();
+        controller
+            .apply(&VoiceShapingConfig {
+                input_gain_db: -6.0,
+                ..VoiceShapingConfig::default()
+            })
+            .unwrap();
+        let mut block = [0.5_f32; 4800];
+        shaper.process(&mut block);
+        assert!((block[4799] - 0.5 * 0.501).abs() < 1e-3);
+
+        let too_many = VoiceShapingConfig {
+            eq: vec![EqBand::default(); MAX_EQ_BANDS + 1],
+            ..VoiceShapingConfig::default()
+        };
+        assert!(controller.apply(&too_many).is_err());
+        assert!(controller.stage(&too_many).is_err());
+    }
+
+    #[test]
+    fn staged_shaping_should_override_queued_updates() {
+        let gain = |input_gain_db: f32| VoiceShapingConfig {
+            input_gain_db,
+            ..VoiceShapingConfig::default()
+        };
+        let (mut controller, mut shaper) = voice_shaper(&gain(0.0), 48_000, 1).unwrap();
+        let staged = controller.stage(&gain(-6.0)).unwrap();
+        // 暂存的参数在应用前不生效
+        let mut block = [0.5_f32; 4800];
+        shaper.process(&mut block);
+        assert!((block[4799] - 0.5).abs() < 1e-3);
+
+        controller.apply(&gain(6.0)).unwrap();
+        shaper.apply_staged(staged);
+        let mut block = [0.5_f32; 4800];
+        shaper.process(&mut block);
+        assert!((block[4799] - 0.5 * 0.501).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stage_state_should_follow_band_identity() {
+        let band = |kind: EqBandKind, gain_db: f32| VoiceShapingConfig {
+            eq: vec![EqBand {
+                kind,
+                frequency_hz: 1000.0,
+                gain_db,
+                q: 1.0,
+            }],
+            ..VoiceShapingConfig::default()
+        };
+        // 喂入正弦后切换参数，再喂静音：输出只剩滤波器残留状态的衰减振荡
+        let ringing = |next: &VoiceShapingConfig| {
+            let (mut controller, mut shaper) =
+                voice_shaper(&band(EqBandKind::Peaking, 6.0), 48_000, 1).unwrap();
+            let mut signal: Vec<f32> = (0..4800)
+                .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48_000.0).sin())
+                .collect();
+            shaper.process(&mut signal);
+            controller.apply(next).unwrap();
+            let mut silence = [0.0_f32; 16];
+            shaper.process(&mut silence);
+            silence.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()))
+        };
+
+        // 同一频段调参沿用状态
+        assert!(ringing(&band(EqBandKind::Peaking, 3.0)) > 1e-3);
+        // 频段类型改变时从零状态开始
+        assert_eq!(ringing(&band(EqBandKind::HighShelf, 6.0)), 0.0);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{
+    error::AppError,
+    types::{
+        AudioRouteConfig, DeviceDirection, DeviceInfo, DeviceList, InputChannel, InputRouteMode,
+        RouteChange, RouteChangeReason,
+    },
+};
+
+/// 某一方向按优先级排列的设备 id：首选设备在前，备用设备依次在后，去掉空值与重复项
+pub fn preference_order(route: &AudioRouteConfig, direction: DeviceDirection) -> Vec<&str> {
+    let (preferred, fallbacks) = match direction {
+        DeviceDirection::Input => (&route.input_device_id, &route.input_fallback_ids),
+        DeviceDirection::Output => (&route.bridge_output_device_id, &route.output_fallback_ids),
+    };
+    let mut order: Vec<&str> = Vec::with_capacity(fallbacks.len() + 1);
+    for id in std::iter::once(preferred).chain(fallbacks) {
+        if !id.is_empty() && !order.contains(&id.as_str()) {
+            order.push(id);
+        }
+    }
+    order
+}
+
+fn first_available<'a>(order: &[&str], devices: &'a [DeviceInfo]) -> Option<&'a DeviceInfo> {
+    order
+        .iter()
+        .find_map(|id| devices.iter().find(|device| device.id == *id))
+}
+
+/// 跟随系统默认输入时的目标设备；虚拟麦克风被设为默认时不跟随，避免把桥接输出再采回来
+fn followed_default<'a>(
+    route: &AudioRouteConfig,
+    devices: &'a DeviceList,
+) -> Option<&'a DeviceInfo> {
+    if route.input_mode != InputRouteMode::FollowDefault {
+        return None;
+    }
+    devices
+        .inputs
+        .iter()
+        .find(|device| device.is_default && !device.is_virtual_candidate)
+}
+
+/// 在当前可用设备中选出实际使用的路由：跟随系统默认输入时优先使用默认设备，
+/// 首选设备不可用时依次退到备用设备。
+///
+/// 声道设置按实际设备收窄，超出其声道数的选择退回默认值，避免备用设备声道较少时启动失败。
+pub fn resolve_active_route(
+    route: &AudioRouteConfig,
+    devices: &DeviceList,
+) -> Result<AudioRouteConfig, AppError> {
+    let input = followed_default(route, devices)
+        .or_else(|| {
+            first_available(
+                &preference_order(route, DeviceDirection::Input),
+                &devices.inputs,
+            )
+        })
+        .ok_or_else(|| AppError::DeviceNotFound("首选与备用输入设备均不可用".to_string()))?;
+    let output = first_available(
+        &preference_order(route, DeviceDirection::Output),
+        &devices.outputs,
+    )
+    .ok_or_else(|| AppError::DeviceNotFound("首选与备用输出设备均不可用".to_string()))?;
+
+    let mut active = route.clone();
+    active.input_device_id = input.id.clone();
+    active.bridge_output_device_id = output.id.clone();
+
+    if let InputChannel::Channel { index } = active.input_channel {
+        if input.channels > 0 && index >= input.channels {
+            active.input_channel = InputChannel::Average;
+        }
+    }
+    if output.channels > 0 && !active.output_channels.is_empty() {
+        active
+            .output_channels
+            .retain(|channel| *channel < output.channels);
+        // 空列表表示写入全部声道，显式选择全部超出时只写第一个声道
+        if active.output_channels.is_empty() {
+            active.output_channels.push(0);
+        }
+    }
+
+    // 参考设备不可用或恰好成为桥接输出时，本次不启用回声消除
+    let reference = &active.reference_output_device_id;
+    if *reference == active.bridge_output_device_id
+        || !devices.outputs.iter().any(|device| device.id == *reference)
+    {
+        active.reference_output_device_id.clear();
+    }
+    Ok(active)
+}
+
+/// 两条实际路由除输入设备与输入声道外是否相同；相同时只需替换输入流，输出流保持运行。
+///
+/// 直通模式会改变处理链的声道数，不属于输入侧设置。
+pub fn same_output_side(a: &AudioRouteConfig, b: &AudioRouteConfig) -> bool {
+    let output_side = |route: &AudioRouteConfig| AudioRouteConfig {
+        input_mode: InputRouteMode::default(),
+        input_device_id: String::new(),
+        input_channel: InputChannel::default(),
+        input_fallback_ids: Vec::new(),
+        output_fallback_ids: Vec::new(),
+        ..route.clone()
+    };
+    output_side(a) == output_side(b)
+}
+
+/// 对比切换前后实际使用的设备，生成 `route_changed` 事件。
+///
+/// 跟随系统默认输入且新设备为默认设备时视为默认设备变化；新设备的优先级高于原设备时
+/// 视为切回首选，否则视为原设备丢失后的故障转移。
+pub fn route_changes(
+    preferred: &AudioRouteConfig,
+    from: &AudioRouteConfig,
+    to: &AudioRouteConfig,
+    devices: &DeviceList,
+) -> Vec<RouteChange> {
+    let slots = [
+        (
+            DeviceDirection::Input,
+            &from.input_device_id,
+            &to.input_device_id,
+            &devices.inputs,
+        ),
+        (
+            DeviceDirection::Output,
+            &from.bridge_output_device_id,
+            &to.bridge_output_device_id,
+            &devices.outputs,
+        ),
+    ];
+
+    let mut changes = Vec::new();
+    for (direction, from_id, to_id, candidates) in slots {
+        if from_id == to_id {
+            continue;
+        }
+        let order = preference_order(preferred, direction);
+        let rank = |id: &str| order.iter().position(|item| *item == id);
+        let followed = direction == DeviceDirection::Input
+            && followed_default(preferred, devices).is_some_and(|device| device.id == *to_id);
+        let reason = match (rank(to_id), rank(from_id)) {
+            _ if followed => RouteChangeReason::DefaultChanged,
+            (Some(to_rank), Some(from_rank)) if to_rank < from_rank => {
+                RouteChangeReason::PreferredRestored
+            }
+            (Some(_), None) => RouteChangeReason::PreferredRestored,
+            _ => RouteChangeReason::DeviceLost,
+        };
+        changes.push(RouteChange {
+            direction,
+            from_device_id: from_id.clone(),
+            to_device_id: to_id.clone(),
+            to_device_name: candidates
+                .iter()
+                .find(|device| device.id == *to_id)
+                .map(|device| device.name.clone())
+                .unwrap_or_default(),
+            reason,
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DeviceFingerprint;
+
+    fn device(id: &str, direction: DeviceDirection, channels: u16) -> DeviceInfo {
+        DeviceInfo {
+            id: id.to_string(),
+            name: format!("设备 {id}"),
+            direction,
+            is_default: false,
+            is_virtual_candidate: false,
+            channels,
+            fingerprint: DeviceFingerprint {
+                direction,
+                name: format!("设备 {id}"),
+                host: "WASAPI".to_string(),
+                endpoint_id: None,
+                configs: Vec::new(),
+            },
+        }
+    }
+
+    fn devices(inputs: &[(&str, u16)], outputs: &[(&str, u16)]) -> DeviceList {
+        DeviceList {
+            inputs: inputs
+                .iter()
+                .map(|(id, channels)| device(id, DeviceDirection::Input, *channels))
+                .collect(),
+            outputs: outputs
+                .iter()
+                .map(|(id, channels)| device(id, DeviceDirection::Output, *channels))
+                .collect(),
+        }
+    }
+
+    fn route() -> AudioRouteConfig {
+        AudioRouteConfig {
+            input_device_id: "headset".to_string(),
+            bridge_output_device_id: "cable".to_string(),
+            reference_output_device_id: "speakers".to_string(),
+            input_channel: InputChannel::Channel { index: 3 },
+            output_channels: vec![0, 5],
+            input_fallback_ids: vec!["webcam".to_string(), "builtin".to_string()],
+            output_fallback_ids: vec!["speakers".to_string()],
+            ..AudioRouteConfig::default()
+        }
+    }
+
+    #[test]
+    fn missing_preferred_devices_should_fall_back_in_order() {
+        let available = devices(
+            &[("headset", 4), ("builtin", 2), ("webcam", 1)],
+            &[("cable", 8), ("speakers", 2)],
+        );
+        let active = resolve_active_route(&route(), &available).unwrap();
+        assert_eq!(active.input_device_id, "headset");
+        assert_eq!(active.input_channel, InputChannel::Channel { index: 3 });
+        assert_eq!(active.output_channels, [0, 5]);
+        assert_eq!(active.reference_output_device_id, "speakers");
+
+        // 耳机与虚拟线缆都拔掉：输入退到摄像头麦克风，输出退到扬声器，
+        // 声道设置按备用设备收窄，参考设备与输出相同时停用回声消除
+        let degraded = devices(&[("builtin", 2), ("webcam", 1)], &[("speakers", 2)]);
+        let active = resolve_active_route(&route(), &degraded).unwrap();
+        assert_eq!(active.input_device_id, "webcam");
+        assert_eq!(active.bridge_output_device_id, "speakers");
+        assert_eq!(active.input_channel, InputChannel::Average);
+        assert_eq!(active.output_channels, [0]);
+        assert!(active.reference_output_device_id.is_empty());
+
+        // 选中的声道在备用设备上都不存在时退到第一个声道，而不是写入全部声道
+        let high = AudioRouteConfig {
+            output_channels: vec![4, 5],
+            ..route()
+        };
+        let active = resolve_active_route(&high, &degraded).unwrap();
+        assert_eq!(active.output_channels, [0]);
+
+        let empty = devices(&[("other", 1)], &[("speakers", 2)]);
+        assert!(resolve_active_route(&route(), &empty).is_err());
+    }
+
+    #[test]
+    fn follow_default_should_track_system_default_input() {
+        let follow = AudioRouteConfig {
+            input_mode: InputRouteMode::FollowDefault,
+            ..route()
+        };
+        let mut available = devices(&[("headset", 1), ("usb", 2)], &[("cable", 2)]);
+        available.inputs[1].is_default = true;
+        let previous = resolve_active_route(&follow, &available).unwrap();
+        assert_eq!(previous.input_device_id, "usb");
+
+        available.inputs[1].is_default = false;
+        available.inputs[0].is_default = true;
+        let next = resolve_active_route(&follow, &available).unwrap();
+        assert_eq!(next.input_device_id, "headset");
+        let changes = route_changes(&follow, &previous, &next, &available);
+        assert_eq!(changes[0].reason, RouteChangeReason::DefaultChanged);
+
+        // 虚拟麦克风成为默认设备时不跟随，退回首选设备列表
+        available.inputs[0].is_default = false;
+        available
+            .inputs
+            .push(device("cable-out", DeviceDirection::Input, 2));
+        available.inputs[2].is_default = true;
+        available.inputs[2].is_virtual_candidate = true;
+        let active = resolve_active_route(&follow, &available).unwrap();
+        assert_eq!(active.input_device_id, "headset");
+    }
+
+    #[test]
+    fn input_side_changes_should_keep_output_side() {
+        let active = route();
+        let mut next = active.clone();
+        next.input_device_id = "webcam".to_string();
+        next.input_channel = InputChannel::Average;
+        next.input_mode = InputRouteMode::FollowDefault;
+        next.output_fallback_ids.clear();
+        assert!(same_output_side(&active, &next));
+
+        next.passthrough = true;
+        assert!(!same_output_side(&active, &next));
+        next.passthrough = false;
+        next.reference_output_device_id.clear();
+        assert!(!same_output_side(&active, &next));
+    }
+
+    #[test]
+    fn route_changes_should_explain_failover_and_restore() {
+        let available = devices(&[("headset", 1), ("webcam", 1)], &[("cable", 2)]);
+        let preferred = route();
+        let mut fallback = preferred.clone();
+        fallback.input_device_id = "webcam".to_string();
+
+        let lost = route_changes(&preferred, &preferred, &fallback, &available);
+        assert_eq!(lost.len(), 1);
+        assert_eq!(lost[0].direction, DeviceDirection::Input);
+        assert_eq!(lost[0].to_device_name, "设备 webcam");
+        assert_eq!(lost[0].reason, RouteChangeReason::DeviceLost);
+
+        let restored = route_changes(&preferred, &fallback, &preferred, &available);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].to_device_id, "headset");
+        assert_eq!(restored[0].reason, RouteChangeReason::PreferredRestored);
+
+        assert!(route_changes(&preferred, &preferred, &preferred, &available).is_empty());
+    }
+}
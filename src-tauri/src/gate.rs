@@ -1,37 +1,190 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        OnceLock,
+    },
+    thread::Thread,
+    time::{Duration, Instant},
+};
 
 use chrono::Utc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
-use crate::types::{GateMode, GateState};
+#[cfg(any(target_os = "windows", test))]
+use crate::types::{EnvelopeConfig, EnvelopeCurve};
+use crate::types::{GateMode, GateState, VoxOverride};
 
 #[derive(Debug)]
 pub struct GateController {
     is_open: AtomicBool,
+    /// 延迟闭麦令牌，每次开闭状态变更都会递增以作废未决的延迟闭麦
+    close_token: AtomicU64,
     state: RwLock<GateState>,
+    hybrid: Mutex<HybridKey>,
+    /// 声控检测器的最近判定
+    vox_open: AtomicBool,
+    /// 语音活动检测器的最近判定
+    vad_open: AtomicBool,
+    /// 自动模式（声控/语音检测）下快捷键正在覆盖判定，仅在持有 `state` 写锁时读写
+    vox_overridden: AtomicBool,
+    /// 检测器判定有待应用
+    detector_dirty: AtomicBool,
+    /// 应用检测器判定的线程，判定翻转时唤醒
+    detector_applier: OnceLock<Thread>,
 }
 
 impl GateController {
     pub fn new(initial_mode: GateMode) -> Self {
-        let mut state = GateState::default();
-        state.mode = initial_mode;
+        let state = GateState {
+            is_open: idle_open(&initial_mode, false),
+            mode: initial_mode,
+            ..GateState::default()
+        };
         Self {
             is_open: AtomicBool::new(state.is_open),
+            close_token: AtomicU64::new(0),
             state: RwLock::new(state),
+            hybrid: Mutex::new(HybridKey::default()),
+            vox_open: AtomicBool::new(false),
+            vad_open: AtomicBool::new(false),
+            vox_overridden: AtomicBool::new(false),
+            detector_dirty: AtomicBool::new(false),
+            detector_applier: OnceLock::new(),
         }
     }
 
     pub fn set_mode(&self, mode: GateMode) {
         let mut state = self.state.write();
+        if state.mode != mode {
+            // 切换模式后回到新模式的空闲状态，例如按键静音默认开麦
+            let detector_open = self.detector_open(&mode);
+            self.apply_open(&mut state, idle_open(&mode, detector_open), "mode");
+            *self.hybrid.lock() = HybridKey::default();
+            self.vox_overridden.store(false, Ordering::SeqCst);
+        }
         state.mode = mode;
         state.changed_at = Utc::now();
         state.last_source = "mode".to_string();
     }
 
+    /// 声控检测器状态翻转时由音频线程调用：只记录判定并唤醒应用线程，不加锁也不分配
+    #[cfg(any(target_os = "windows", test))]
+    pub fn vox_decision(&self, open: bool) {
+        self.vox_open.store(open, Ordering::SeqCst);
+        self.wake_detector_applier();
+    }
+
+    /// 语音活动检测器状态翻转时由音频线程调用，同 [`Self::vox_decision`]
+    #[cfg(any(target_os = "windows", test))]
+    pub fn vad_decision(&self, open: bool) {
+        self.vad_open.store(open, Ordering::SeqCst);
+        self.wake_detector_applier();
+    }
+
+    #[cfg(any(target_os = "windows", test))]
+    fn wake_detector_applier(&self) {
+        self.detector_dirty.store(true, Ordering::SeqCst);
+        if let Some(applier) = self.detector_applier.get() {
+            applier.unpark();
+        }
+    }
+
+    /// 注册应用检测器判定的线程，见 [`Self::apply_detectors`]
+    pub fn set_detector_applier(&self, applier: Thread) {
+        let _ = self.detector_applier.set(applier);
+    }
+
+    /// 在音频线程之外应用当前模式对应检测器的最近判定；仅在声控/语音检测模式且未被
+    /// 快捷键覆盖时生效，返回门控状态是否改变
+    pub fn apply_detectors(&self) -> bool {
+        if !self.detector_dirty.swap(false, Ordering::SeqCst) {
+            return false;
+        }
+        let mut state = self.state.write();
+        let mode = state.mode.clone();
+        if self.vox_overridden.load(Ordering::SeqCst)
+            || !matches!(mode, GateMode::Vox | GateMode::Vad)
+        {
+            return false;
+        }
+        let open = self.detector_open(&mode);
+        if state.is_open == open {
+            return false;
+        }
+        self.apply_open(&mut state, open, detector_source(&mode));
+        true
+    }
+
+    fn detector_open(&self, mode: &GateMode) -> bool {
+        match mode {
+            GateMode::Vad => self.vad_open.load(Ordering::SeqCst),
+            _ => self.vox_open.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 自动模式下快捷键按下/松开：按住期间强制开闭麦，松开后恢复检测器判定
+    pub fn override_vox(&self, action: &VoxOverride, pressed: bool, source: &str) {
+        let forced = match action {
+            VoxOverride::Off => return,
+            VoxOverride::Mute => false,
+            VoxOverride::Talk => true,
+        };
+        let mut state = self.state.write();
+        self.vox_overridden.store(pressed, Ordering::SeqCst);
+        if pressed {
+            self.apply_open(&mut state, forced, source);
+        } else {
+            let mode = state.mode.clone();
+            self.apply_open(
+                &mut state,
+                self.detector_open(&mode),
+                detector_source(&mode),
+            );
+        }
+    }
+
+    /// 混合模式按下：未锁定时开麦，已锁定时解锁并闭麦
+    pub fn hybrid_press(&self, now: Instant, source: &str) {
+        let mut key = self.hybrid.lock();
+        if let Some(open) = key.press(now, self.is_open()) {
+            self.set_open(open, source);
+        }
+    }
+
+    /// 混合模式松开，返回 `true` 表示本次为长按，调用方应按按住说话逻辑闭麦
+    pub fn hybrid_release(&self, now: Instant, tap_threshold: Duration) -> bool {
+        self.hybrid.lock().release(now, tap_threshold)
+    }
+
     pub fn set_open(&self, open: bool, source: &str) {
-        self.is_open.store(open, Ordering::SeqCst);
         let mut state = self.state.write();
+        self.apply_open(&mut state, open, source);
+    }
+
+    /// 安排在 `hang` 之后闭麦并返回令牌；在此之前的任何开闭操作都会取消本次安排
+    pub fn schedule_close(&self, hang: Duration) -> u64 {
+        let mut state = self.state.write();
+        let token = self.close_token.fetch_add(1, Ordering::SeqCst) + 1;
+        state.pending_close_at =
+            Some(Utc::now() + chrono::Duration::from_std(hang).unwrap_or_default());
+        token
+    }
+
+    /// 令牌仍有效时执行延迟闭麦，返回是否真正关闭
+    pub fn commit_close(&self, token: u64, source: &str) -> bool {
+        let mut state = self.state.write();
+        if self.close_token.load(Ordering::SeqCst) != token {
+            return false;
+        }
+        self.apply_open(&mut state, false, source);
+        true
+    }
+
+    fn apply_open(&self, state: &mut GateState, open: bool, source: &str) {
+        self.close_token.fetch_add(1, Ordering::SeqCst);
+        self.is_open.store(open, Ordering::SeqCst);
         state.is_open = open;
+        state.pending_close_at = None;
         state.changed_at = Utc::now();
         state.last_source = source.to_string();
     }
@@ -50,25 +203,137 @@ impl GateController {
     }
 }
 
-#[allow(dead_code)]
-pub fn apply_envelope(
-    current_gain: &mut f32,
-    target_open: bool,
-    frame_count: usize,
-    sample_rate: u32,
-) {
-    let attack_release_ms = 8.0_f32;
-    let step = if sample_rate == 0 {
-        1.0
-    } else {
-        1.0 / ((attack_release_ms / 1000.0) * sample_rate as f32)
-    };
-
-    for _ in 0..frame_count {
-        if target_open {
-            *current_gain = (*current_gain + step).min(1.0);
+/// 未按下任何按键时的门控状态：按键静音默认开麦，自动模式跟随检测器判定
+fn idle_open(mode: &GateMode, detector_open: bool) -> bool {
+    match mode {
+        GateMode::PushToMute => true,
+        GateMode::Vox | GateMode::Vad => detector_open,
+        _ => false,
+    }
+}
+
+fn detector_source(mode: &GateMode) -> &'static str {
+    match mode {
+        GateMode::Vad => "vad",
+        _ => "vox",
+    }
+}
+
+/// 混合模式按键状态机：短按锁定开麦，长按视为按住说话，锁定时再次按下解锁。
+#[derive(Debug, Default)]
+pub struct HybridKey {
+    pressed_at: Option<Instant>,
+    latched: bool,
+    /// 本次按下用于解锁，松开时不再处理
+    unlatching: bool,
+}
+
+impl HybridKey {
+    /// 返回需要设置的门控状态；按键自动重复产生的多次按下会被忽略
+    pub fn press(&mut self, now: Instant, is_open: bool) -> Option<bool> {
+        if self.pressed_at.is_some() {
+            return None;
+        }
+        self.pressed_at = Some(now);
+
+        if self.latched && is_open {
+            self.latched = false;
+            self.unlatching = true;
+            return Some(false);
+        }
+        self.latched = false;
+        Some(true)
+    }
+
+    /// 返回 `true` 表示长按松开需要闭麦；短按则进入锁定状态
+    pub fn release(&mut self, now: Instant, tap_threshold: Duration) -> bool {
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return false;
+        };
+        if std::mem::take(&mut self.unlatching) {
+            return false;
+        }
+
+        if now.saturating_duration_since(pressed_at) < tap_threshold {
+            self.latched = true;
+            false
         } else {
-            *current_gain = (*current_gain - step).max(0.0);
+            true
+        }
+    }
+}
+
+/// 门控增益包络，跨输出回调保持状态并逐样本平滑过渡，避免开闭麦爆音。
+#[cfg(any(target_os = "windows", test))]
+pub struct GainEnvelope {
+    channels: usize,
+    /// 线性进度 0.0（全闭）..=1.0（全开），再经曲线映射为增益
+    position: f32,
+    attack_step: f32,
+    release_step: f32,
+    curve: EnvelopeCurve,
+}
+
+#[cfg(any(target_os = "windows", test))]
+impl GainEnvelope {
+    pub fn new(config: &EnvelopeConfig, sample_rate: u32, channels: usize) -> Self {
+        let step = |ms: u32| {
+            let samples = ms as f32 / 1000.0 * sample_rate as f32;
+            if samples < 1.0 {
+                1.0
+            } else {
+                1.0 / samples
+            }
+        };
+
+        Self {
+            channels: channels.max(1),
+            position: 0.0,
+            attack_step: step(config.attack_ms),
+            release_step: step(config.release_ms),
+            curve: config.curve.clone(),
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        shape(&self.curve, self.position)
+    }
+
+    /// 朝 `target_open` 推进包络，并把逐帧增益乘到交错的 `samples` 上
+    pub fn process(&mut self, target_open: bool, samples: &mut [f32]) {
+        let settled = if target_open {
+            self.position >= 1.0
+        } else {
+            self.position <= 0.0
+        };
+        if settled {
+            if !target_open {
+                samples.fill(0.0);
+            }
+            return;
+        }
+
+        for frame in samples.chunks_exact_mut(self.channels) {
+            if target_open {
+                self.position = (self.position + self.attack_step).min(1.0);
+            } else {
+                self.position = (self.position - self.release_step).max(0.0);
+            }
+            let gain = shape(&self.curve, self.position);
+            frame.iter_mut().for_each(|sample| *sample *= gain);
+        }
+    }
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn shape(curve: &EnvelopeCurve, position: f32) -> f32 {
+    match curve {
+        EnvelopeCurve::Linear => position,
+        EnvelopeCurve::EqualPower => (position * std::f32::consts::FRAC_PI_2).sin(),
+        EnvelopeCurve::Exponential => {
+            // 归一化指数曲线，覆盖约 60dB 动态范围且在 0 处连续
+            let k = 1000.0_f32.ln();
+            ((k * position).exp() - 1.0) / (k.exp() - 1.0)
         }
     }
 }
@@ -87,12 +352,150 @@ mod tests {
         assert!(!gate.is_open());
     }
 
+    #[test]
+    fn hybrid_tap_should_latch_and_hold_should_act_as_ptt() {
+        let threshold = Duration::from_millis(250);
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+        let mut key = HybridKey::default();
+
+        // 短按：开麦并锁定
+        assert_eq!(key.press(at(0), false), Some(true));
+        assert!(!key.release(at(120), threshold));
+
+        // 锁定时再次按下：立即闭麦，松开不再处理
+        assert_eq!(key.press(at(1000), true), Some(false));
+        assert_eq!(key.press(at(1030), false), None);
+        assert!(!key.release(at(1500), threshold));
+
+        // 长按：开麦，松开闭麦且不锁定
+        assert_eq!(key.press(at(2000), false), Some(true));
+        assert!(key.release(at(2600), threshold));
+        assert_eq!(key.press(at(3000), false), Some(true));
+    }
+
+    #[test]
+    fn hybrid_controller_should_follow_key_events() {
+        let threshold = Duration::from_millis(250);
+        let t0 = Instant::now();
+        let gate = GateController::new(GateMode::Hybrid);
+
+        gate.hybrid_press(t0, "test");
+        assert!(!gate.hybrid_release(t0 + Duration::from_millis(100), threshold));
+        assert!(gate.is_open());
+
+        gate.hybrid_press(t0 + Duration::from_millis(800), "test");
+        assert!(!gate.is_open());
+        assert!(!gate.hybrid_release(t0 + Duration::from_millis(900), threshold));
+        assert!(!gate.is_open());
+    }
+
+    #[test]
+    fn push_to_mute_should_start_open() {
+        let gate = GateController::new(GateMode::PushToMute);
+        assert!(gate.is_open());
+
+        gate.set_mode(GateMode::Ptt);
+        assert!(!gate.is_open());
+        gate.set_mode(GateMode::PushToMute);
+        assert!(gate.is_open());
+    }
+
+    #[test]
+    fn hotkey_should_override_vox_while_held() {
+        let gate = GateController::new(GateMode::Vox);
+        gate.vox_decision(true);
+        assert!(!gate.is_open());
+        assert!(gate.apply_detectors());
+        assert!(gate.is_open());
+
+        gate.override_vox(&VoxOverride::Mute, true, "test");
+        assert!(!gate.is_open());
+        gate.vox_decision(false);
+        gate.vox_decision(true);
+        assert!(!gate.apply_detectors());
+        assert!(!gate.is_open());
+
+        gate.override_vox(&VoxOverride::Mute, false, "test");
+        assert!(gate.is_open());
+        assert_eq!(gate.snapshot().last_source, "vox");
+
+        // 非声控模式下检测器判定不影响门控
+        gate.set_mode(GateMode::Ptt);
+        gate.vox_decision(true);
+        assert!(!gate.apply_detectors());
+        assert!(!gate.is_open());
+
+        // 切到语音检测模式后跟随语音检测器判定
+        gate.vad_decision(true);
+        gate.set_mode(GateMode::Vad);
+        assert!(gate.is_open());
+        gate.vad_decision(false);
+        assert!(gate.is_open());
+        assert!(gate.apply_detectors());
+        assert!(!gate.is_open());
+        assert_eq!(gate.snapshot().last_source, "vad");
+    }
+
+    #[test]
+    fn press_during_hang_should_cancel_pending_close() {
+        let gate = GateController::new(GateMode::Ptt);
+        gate.set_open(true, "test");
+
+        let token = gate.schedule_close(Duration::from_millis(300));
+        assert!(gate.snapshot().pending_close_at.is_some());
+        gate.set_open(true, "test");
+        assert!(!gate.commit_close(token, "test"));
+        assert!(gate.is_open());
+        assert!(gate.snapshot().pending_close_at.is_none());
+
+        let token = gate.schedule_close(Duration::from_millis(300));
+        assert!(gate.commit_close(token, "test"));
+        assert!(!gate.is_open());
+    }
+
     #[test]
     fn envelope_should_reach_bounds() {
-        let mut gain = 0.0;
-        apply_envelope(&mut gain, true, 960, 48_000);
-        assert!(gain > 0.0);
-        apply_envelope(&mut gain, false, 9600, 48_000);
-        assert!(gain <= 0.01);
+        let config = EnvelopeConfig {
+            attack_ms: 8,
+            release_ms: 8,
+            curve: EnvelopeCurve::Linear,
+        };
+        let mut envelope = GainEnvelope::new(&config, 48_000, 1);
+        envelope.process(true, &mut [1.0; 960]);
+        assert!(envelope.gain() > 0.0);
+        envelope.process(false, &mut [1.0; 9600]);
+        assert!(envelope.gain() <= 0.01);
+    }
+
+    #[test]
+    fn envelope_should_ramp_across_blocks_without_jumps() {
+        for curve in [
+            EnvelopeCurve::Linear,
+            EnvelopeCurve::EqualPower,
+            EnvelopeCurve::Exponential,
+        ] {
+            let config = EnvelopeConfig {
+                attack_ms: 10,
+                release_ms: 20,
+                curve,
+            };
+            let mut envelope = GainEnvelope::new(&config, 48_000, 1);
+            let mut output = Vec::new();
+            for open in [true, true, true, false, false, false, false, false] {
+                let mut block = [1.0_f32; 256];
+                envelope.process(open, &mut block);
+                output.extend_from_slice(&block);
+            }
+
+            assert_eq!(output[0], shape(&config.curve, 1.0 / 480.0));
+            assert!((output[256 * 3 - 1] - 1.0).abs() < 1e-6);
+            assert_eq!(*output.last().unwrap(), 0.0);
+            let max_jump = output
+                .windows(2)
+                .map(|w| (w[1] - w[0]).abs())
+                .fold(0.0_f32, f32::max);
+            assert!(max_jump < 0.02, "max_jump = {max_jump}");
+        }
     }
 }
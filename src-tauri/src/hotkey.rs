@@ -1,4 +1,9 @@
-use std::{str::FromStr, sync::Arc};
+use std::{
+    str::FromStr,
+    sync::{mpsc, Arc, OnceLock},
+    thread,
+    time::{Duration, Instant},
+};
 
 use parking_lot::RwLock;
 use tauri::Emitter;
@@ -11,20 +16,12 @@ use crate::{
     types::{GateMode, HotkeyConfig},
 };
 
+#[derive(Default)]
 pub struct HotkeyManager {
     current: RwLock<Option<String>>,
     mouse_hook: MouseHookManager,
 }
 
-impl Default for HotkeyManager {
-    fn default() -> Self {
-        Self {
-            current: RwLock::new(None),
-            mouse_hook: MouseHookManager::default(),
-        }
-    }
-}
-
 impl HotkeyManager {
     pub fn apply(
         &self,
@@ -43,8 +40,7 @@ impl HotkeyManager {
         self.mouse_hook.unregister();
 
         if is_mouse_accelerator(&config.accelerator) {
-            self.mouse_hook
-                .register(app, &config.accelerator, gate, config.mode.clone())?;
+            self.mouse_hook.register(app, config, gate)?;
             *self.current.write() = None;
             return Ok(());
         }
@@ -55,9 +51,9 @@ impl HotkeyManager {
         manager
             .on_shortcut(shortcut, {
                 let app = app.clone();
-                let mode = config.mode.clone();
+                let config = config.clone();
                 move |_app, _shortcut, event| {
-                    handle_event(&app, &gate, &mode, event.state);
+                    handle_event(&app, &gate, &config, event.state);
                 }
             })
             .map_err(|e| AppError::Hotkey(format!("注册快捷键失败: {e}")))?;
@@ -67,18 +63,108 @@ impl HotkeyManager {
     }
 }
 
+/// 按住说话松键：按配置的挂起时长延迟闭麦，挂起期间再次按下会取消本次闭麦
+pub fn release_gate(
+    app: &tauri::AppHandle,
+    gate: &Arc<GateController>,
+    hang_time_ms: u32,
+    source: &'static str,
+) {
+    if hang_time_ms == 0 {
+        gate.set_open(false, source);
+        return;
+    }
+
+    let hang = Duration::from_millis(hang_time_ms as u64);
+    let token = gate.schedule_close(hang);
+    let pending = PendingClose {
+        deadline: Instant::now() + hang,
+        token,
+        source,
+        gate: gate.clone(),
+        app: app.clone(),
+    };
+    if close_timer().send(pending).is_err() {
+        log::error!("延迟闭麦线程已退出");
+    }
+}
+
+struct PendingClose {
+    deadline: Instant,
+    token: u64,
+    source: &'static str,
+    gate: Arc<GateController>,
+    app: tauri::AppHandle,
+}
+
+static CLOSE_TIMER: OnceLock<mpsc::Sender<PendingClose>> = OnceLock::new();
+
+/// 所有松键共用的延迟闭麦线程，首次使用时启动
+fn close_timer() -> &'static mpsc::Sender<PendingClose> {
+    CLOSE_TIMER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<PendingClose>();
+        thread::spawn(move || run_close_timer(rx));
+        tx
+    })
+}
+
+fn run_close_timer(rx: mpsc::Receiver<PendingClose>) {
+    // 新的松键会让门控换发新的令牌，旧的挂起闭麦随之失效，因此只需记住最近一次
+    let mut pending: Option<PendingClose> = None;
+    loop {
+        let next = match pending.as_ref() {
+            Some(close) => {
+                let wait = close.deadline.saturating_duration_since(Instant::now());
+                match rx.recv_timeout(wait) {
+                    Ok(next) => Some(next),
+                    Err(mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            None => match rx.recv() {
+                Ok(next) => Some(next),
+                Err(_) => return,
+            },
+        };
+
+        if next.is_some() {
+            pending = next;
+            continue;
+        }
+
+        if let Some(close) = pending.take() {
+            if close.gate.commit_close(close.token, close.source) {
+                let _ = close.app.emit("gate_state_changed", close.gate.snapshot());
+            }
+        }
+    }
+}
+
+/// 混合模式松键：长按按住说话逻辑（含挂起时长）闭麦，短按保持锁定
+pub fn hybrid_release(
+    app: &tauri::AppHandle,
+    gate: &Arc<GateController>,
+    config: &HotkeyConfig,
+    source: &'static str,
+) {
+    let threshold = Duration::from_millis(config.tap_threshold_ms as u64);
+    if gate.hybrid_release(Instant::now(), threshold) {
+        release_gate(app, gate, config.hang_time_ms, source);
+    }
+}
+
 fn handle_event(
     app: &tauri::AppHandle,
-    gate: &GateController,
-    mode: &GateMode,
+    gate: &Arc<GateController>,
+    config: &HotkeyConfig,
     state: ShortcutState,
 ) {
-    match mode {
+    match config.mode {
         GateMode::Ptt => {
             if state == ShortcutState::Pressed {
                 gate.set_open(true, "hotkey");
             } else if state == ShortcutState::Released {
-                gate.set_open(false, "hotkey");
+                release_gate(app, gate, config.hang_time_ms, "hotkey");
             }
         }
         GateMode::Toggle => {
@@ -86,9 +172,25 @@ fn handle_event(
                 gate.toggle("hotkey");
             }
         }
+        GateMode::PushToMute => {
+            if state == ShortcutState::Pressed {
+                gate.set_open(false, "hotkey");
+            } else if state == ShortcutState::Released {
+                gate.set_open(true, "hotkey");
+            }
+        }
+        GateMode::Vox | GateMode::Vad => {
+            if state == ShortcutState::Pressed {
+                gate.override_vox(&config.vox_override, true, "hotkey");
+            } else if state == ShortcutState::Released {
+                gate.override_vox(&config.vox_override, false, "hotkey");
+            }
+        }
         GateMode::Hybrid => {
             if state == ShortcutState::Pressed {
-                gate.toggle("hotkey");
+                gate.hybrid_press(Instant::now(), "hotkey");
+            } else if state == ShortcutState::Released {
+                hybrid_release(app, gate, config, "hotkey");
             }
         }
     }
@@ -0,0 +1,427 @@
+use std::{
+    f32::consts::FRAC_PI_2,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use ringbuf::{
+    traits::{Consumer, Observer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+
+/// 切换输入设备时的交叉淡化时长（毫秒）
+const CROSSFADE_MS: u32 = 40;
+/// 开始交叉淡化前新输入源至少攒下的时长，保证淡化期间每块都有新数据可混
+const PREFILL_MS: u32 = 20;
+/// 新输入源暂存上限（毫秒），旧输入源迟迟不开始淡化时丢弃放不下的新数据
+const INCOMING_MS: u32 = 500;
+/// 命令与回收队列容量；切换由控制线程串行发起，每次最多两条命令
+const QUEUE_CAPACITY: usize = 8;
+
+/// 表示没有进行中的切换；输入源编号从 1 开始
+pub const NO_SOURCE: u64 = 0;
+
+/// 控制线程与各路输入流回调共享的切换状态
+pub struct SwitchSignal {
+    /// 当前持有处理链的输入源
+    active: AtomicU64,
+    /// 进行中的切换目标，完成或取消后回到 `NO_SOURCE`
+    pending: AtomicU64,
+}
+
+impl SwitchSignal {
+    pub fn active(&self) -> u64 {
+        self.active.load(Ordering::Acquire)
+    }
+}
+
+/// 一次切换中旧输入源持有的一端：读取新输入源暂存的数据，交接时把处理链放入 `handoff`
+struct Pending<T> {
+    source: u64,
+    samples: HeapCons<f32>,
+    handoff: HeapProd<T>,
+}
+
+enum Command<T> {
+    Begin(Pending<T>),
+    Cancel,
+}
+
+/// 音频线程不再使用的缓冲，送回控制线程释放，避免实时线程释放内存；只为持有到释放，不会读取
+enum Garbage<T> {
+    Pending {
+        _samples: HeapCons<f32>,
+        _handoff: HeapProd<T>,
+    },
+    Backlog {
+        _samples: HeapCons<f32>,
+    },
+}
+
+/// 创建输入源切换器，返回控制端与随处理链移动的音频线程端
+pub fn input_switch<T>(
+    source: u64,
+    sample_rate: u32,
+    channels: usize,
+) -> (SwitchControl<T>, InputSwitch<T>) {
+    let frames = |ms: u32| (sample_rate as usize * ms as usize / 1000).max(1);
+    let channels = channels.max(1);
+    let signal = Arc::new(SwitchSignal {
+        active: AtomicU64::new(source),
+        pending: AtomicU64::new(NO_SOURCE),
+    });
+    let (commands_tx, commands_rx) = HeapRb::<Command<T>>::new(QUEUE_CAPACITY).split();
+    let (garbage_tx, garbage_rx) = HeapRb::<Garbage<T>>::new(QUEUE_CAPACITY).split();
+    (
+        SwitchControl {
+            signal: signal.clone(),
+            commands: commands_tx,
+            garbage: garbage_rx,
+            staged: None,
+            capacity: frames(INCOMING_MS) * channels,
+        },
+        InputSwitch {
+            signal,
+            commands: commands_rx,
+            garbage: garbage_tx,
+            channels,
+            fade_len: frames(CROSSFADE_MS),
+            prefill: frames(PREFILL_MS),
+            pending: None,
+            fade_pos: None,
+            backlog: None,
+        },
+    )
+}
+
+/// 控制线程端：准备新输入源、发起与取消切换
+pub struct SwitchControl<T> {
+    signal: Arc<SwitchSignal>,
+    commands: HeapProd<Command<T>>,
+    garbage: HeapCons<Garbage<T>>,
+    /// 已准备、尚未发给音频线程的切换
+    staged: Option<Pending<T>>,
+    capacity: usize,
+}
+
+impl<T> SwitchControl<T> {
+    #[cfg(target_os = "windows")]
+    pub fn signal(&self) -> Arc<SwitchSignal> {
+        self.signal.clone()
+    }
+
+    pub fn active(&self) -> u64 {
+        self.signal.active()
+    }
+
+    /// 音频线程是否还在处理上一次 `begin`
+    pub fn is_pending(&self) -> bool {
+        self.signal.pending.load(Ordering::Acquire) != NO_SOURCE
+    }
+
+    /// 为输入源 `source` 准备暂存与交接通道，返回交给新输入流回调的一端；
+    /// 新输入流启动成功后再调用 `begin`
+    pub fn prepare(&mut self, source: u64) -> Incoming<T> {
+        self.collect_garbage();
+        let (samples_tx, samples_rx) = HeapRb::<f32>::new(self.capacity).split();
+        let (handoff_tx, handoff_rx) = HeapRb::<T>::new(1).split();
+        self.staged = Some(Pending {
+            source,
+            samples: samples_rx,
+            handoff: handoff_tx,
+        });
+        Incoming {
+            source,
+            signal: self.signal.clone(),
+            samples: samples_tx,
+            handoff: handoff_rx,
+            claimed: false,
+        }
+    }
+
+    /// 把准备好的切换发给当前持有处理链的输入流；队列已满时返回 false
+    pub fn begin(&mut self) -> bool {
+        let Some(pending) = self.staged.take() else {
+            return false;
+        };
+        let source = pending.source;
+        self.signal.pending.store(source, Ordering::Release);
+        if let Err(Command::Begin(pending)) = self.commands.try_push(Command::Begin(pending)) {
+            self.signal.pending.store(NO_SOURCE, Ordering::Release);
+            self.staged = Some(pending);
+            return false;
+        }
+        true
+    }
+
+    /// 放弃尚未完成的切换；音频线程处理后 `is_pending` 变为 false
+    pub fn cancel(&mut self) {
+        self.staged = None;
+        let _ = self.commands.try_push(Command::Cancel);
+    }
+
+    /// 释放音频线程送回的缓冲
+    pub fn collect_garbage(&mut self) {
+        while self.garbage.try_pop().is_some() {}
+    }
+}
+
+/// 新输入流回调持有的一端：接手前暂存数据，旧输入源交出处理链后由它接手
+pub struct Incoming<T> {
+    source: u64,
+    signal: Arc<SwitchSignal>,
+    samples: HeapProd<f32>,
+    handoff: HeapCons<T>,
+    /// 已接手过处理链，之后再被切走时不再暂存
+    claimed: bool,
+}
+
+impl<T> Incoming<T> {
+    #[cfg(target_os = "windows")]
+    pub fn source(&self) -> u64 {
+        self.source
+    }
+
+    /// 新输入流每块调用：尚未轮到自己时暂存 `block` 并返回 None；
+    /// 旧输入源交出处理链后返回它，此时 `block` 未被暂存，应直接送入处理链
+    pub fn offer(&mut self, block: &[f32]) -> Option<T> {
+        if self.claimed {
+            return None;
+        }
+        if self.signal.active() == self.source {
+            if let Some(payload) = self.handoff.try_pop() {
+                self.claimed = true;
+                return Some(payload);
+            }
+        }
+        self.samples.push_slice(block);
+        None
+    }
+}
+
+/// 交叉淡化结束时返回给旧输入源回调，用来把处理链交给新输入源
+pub struct Handover<T> {
+    source: u64,
+    handoff: HeapProd<T>,
+    signal: Arc<SwitchSignal>,
+}
+
+impl<T> Handover<T> {
+    pub fn complete(mut self, payload: T) {
+        // 交接通道容量为 1 且只用一次，写入不会失败
+        let _ = self.handoff.try_push(payload);
+        self.signal.active.store(self.source, Ordering::Release);
+        self.signal.pending.store(NO_SOURCE, Ordering::Release);
+    }
+}
+
+/// 音频线程端：随处理链在各路输入流回调之间移动，只由当前持有处理链的回调调用，不加锁。
+///
+/// 切换期间旧输入流继续驱动处理链，新输入流的数据先暂存到无锁队列；暂存足够后旧输入流在
+/// 自己的块里按等功率曲线淡出、混入暂存的新数据，淡化结束后把处理链交给新输入流，新输入流
+/// 先处理剩余的暂存数据，新输入流的样本既不丢失也不重复。
+pub struct InputSwitch<T> {
+    signal: Arc<SwitchSignal>,
+    commands: HeapCons<Command<T>>,
+    garbage: HeapProd<Garbage<T>>,
+    channels: usize,
+    fade_len: usize,
+    prefill: usize,
+    pending: Option<Pending<T>>,
+    /// 交叉淡化进度（帧），None 表示尚未开始
+    fade_pos: Option<usize>,
+    /// 交叉淡化后剩余的新数据，由新输入源接手后先处理
+    backlog: Option<HeapCons<f32>>,
+}
+
+impl<T> InputSwitch<T> {
+    /// 处理链处理 `block` 之前调用：接手后先补上暂存数据，切换期间做交叉淡化。
+    ///
+    /// 淡化结束时截掉旧输入源本块剩余的数据并返回 `Handover`，调用方处理完本块后交出处理链。
+    pub fn mix(&mut self, block: &mut Vec<f32>) -> Option<Handover<T>> {
+        self.poll_commands();
+        if let Some(mut backlog) = self.backlog.take() {
+            // 预分配的块缓冲足以容纳暂存上限，这里不会重新分配
+            let len = block.len();
+            let queued = backlog.occupied_len();
+            debug_assert!(
+                block.capacity() >= len + queued,
+                "块缓冲容量不足以合并暂存数据"
+            );
+            block.resize(len + queued, 0.0);
+            block.copy_within(0..len, queued);
+            backlog.pop_slice(&mut block[..queued]);
+            self.retire(Garbage::Backlog { _samples: backlog });
+        }
+
+        let pending = self.pending.as_mut()?;
+        let mut pos = match self.fade_pos {
+            Some(pos) => pos,
+            None if pending.samples.occupied_len() >= self.prefill * self.channels => 0,
+            None => return None,
+        };
+        let mut faded = 0;
+        for frame in block.chunks_exact_mut(self.channels) {
+            if pos >= self.fade_len {
+                break;
+            }
+            let t = (pos as f32 + 0.5) / self.fade_len as f32;
+            let (old_gain, new_gain) = ((t * FRAC_PI_2).cos(), (t * FRAC_PI_2).sin());
+            for sample in frame {
+                let new = pending.samples.try_pop().unwrap_or(0.0);
+                *sample = *sample * old_gain + new * new_gain;
+            }
+            pos += 1;
+            faded += 1;
+        }
+        if pos < self.fade_len {
+            self.fade_pos = Some(pos);
+            return None;
+        }
+
+        // 淡化结束，旧输入源本块剩余的数据不再使用
+        block.truncate(faded * self.channels);
+        self.fade_pos = None;
+        let Pending {
+            source,
+            samples,
+            handoff,
+        } = self.pending.take()?;
+        self.backlog = Some(samples);
+        Some(Handover {
+            source,
+            handoff,
+            signal: self.signal.clone(),
+        })
+    }
+
+    fn poll_commands(&mut self) {
+        while let Some(command) = self.commands.try_pop() {
+            let previous = match command {
+                Command::Begin(pending) => self.pending.replace(pending),
+                Command::Cancel => {
+                    self.signal.pending.store(NO_SOURCE, Ordering::Release);
+                    self.pending.take()
+                }
+            };
+            self.fade_pos = None;
+            if let Some(previous) = previous {
+                self.retire(Garbage::Pending {
+                    _samples: previous.samples,
+                    _handoff: previous.handoff,
+                });
+            }
+        }
+    }
+
+    fn retire(&mut self, garbage: Garbage<T>) {
+        if let Err(garbage) = self.garbage.try_push(garbage) {
+            drop(garbage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+    const BLOCK: usize = 480;
+
+    /// 模拟处理链：随切换器在输入源之间移动，记录送入的数据
+    struct Chain {
+        switch: InputSwitch<Box<Chain>>,
+        out: Vec<f32>,
+    }
+
+    /// 模拟一路输入流回调，返回交接时的 `Handover`
+    fn callback(
+        chain: &mut Option<Box<Chain>>,
+        incoming: Option<&mut Incoming<Box<Chain>>>,
+        block: &mut Vec<f32>,
+    ) {
+        if chain.is_none() {
+            *chain = incoming.and_then(|incoming| incoming.offer(block));
+        }
+        let Some(current) = chain.as_mut() else {
+            return;
+        };
+        let handover = current.switch.mix(block);
+        current.out.extend_from_slice(block);
+        if let Some(handover) = handover {
+            if let Some(current) = chain.take() {
+                handover.complete(current);
+            }
+        }
+    }
+
+    fn start() -> (SwitchControl<Box<Chain>>, Option<Box<Chain>>) {
+        let (control, switch) = input_switch(1, RATE, 1);
+        let chain = Box::new(Chain {
+            switch,
+            out: Vec::new(),
+        });
+        (control, Some(chain))
+    }
+
+    #[test]
+    fn crossfade_should_hand_over_without_losing_new_samples() {
+        let (mut control, mut old) = start();
+        let mut incoming = control.prepare(2);
+        assert!(control.begin());
+        assert!(control.is_pending());
+
+        // 旧输入源为常数 1，新输入源为递增序号，两路交替送块
+        let mut new = None;
+        let mut next = 0.0_f32;
+        let mut fed_new = 0;
+        for _ in 0..20 {
+            callback(&mut old, None, &mut vec![1.0; BLOCK]);
+            let mut block: Vec<f32> = (0..BLOCK).map(|i| next + i as f32).collect();
+            block.reserve(BLOCK * 4);
+            next += BLOCK as f32;
+            fed_new += BLOCK;
+            callback(&mut new, Some(&mut incoming), &mut block);
+        }
+
+        assert_eq!(control.active(), 2);
+        assert!(!control.is_pending());
+        assert!(old.is_none());
+        let out = &new.as_ref().unwrap().out;
+        // 旧输入源在攒够预填充前原样输出，之后输出淡化段
+        let fade_len = (RATE / 1000 * CROSSFADE_MS) as usize;
+        assert!(out[..2 * BLOCK].iter().all(|s| *s == 1.0));
+        let after = &out[2 * BLOCK + fade_len..];
+        // 接手后的数据紧接淡化段消耗的样本，一个不少也一个不多
+        assert_eq!(after.len() + fade_len, fed_new);
+        assert_eq!(after[0], fade_len as f32);
+        assert!(after.windows(2).all(|w| w[1] == w[0] + 1.0));
+
+        // 已切走的旧输入源不再驱动处理链
+        callback(&mut old, None, &mut vec![1.0; BLOCK]);
+        assert_eq!(new.as_ref().unwrap().out.len(), out.len());
+        control.collect_garbage();
+    }
+
+    #[test]
+    fn cancelled_switch_should_keep_old_source() {
+        let (mut control, mut old) = start();
+        let mut incoming = control.prepare(2);
+        assert!(control.begin());
+        let mut new = None;
+        callback(&mut new, Some(&mut incoming), &mut vec![0.5; BLOCK]);
+
+        control.cancel();
+        callback(&mut old, None, &mut vec![1.0; BLOCK]);
+        assert!(!control.is_pending());
+        for _ in 0..10 {
+            callback(&mut new, Some(&mut incoming), &mut vec![0.5; BLOCK]);
+            callback(&mut old, None, &mut vec![1.0; BLOCK]);
+        }
+        assert_eq!(control.active(), 1);
+        assert!(new.is_none());
+        assert!(old.unwrap().out.iter().all(|s| *s == 1.0));
+    }
+}
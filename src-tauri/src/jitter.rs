@@ -0,0 +1,166 @@
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+};
+
+use crate::{bridge::BridgeConsumer, resampler::Resampler};
+
+/// 漂移补偿允许的最大步长偏差（±0.5%）
+const MAX_ADJUST: f64 = 0.005;
+/// 水位误差每秒对应的步长修正量
+const ADJUST_GAIN: f64 = 0.25;
+/// 每次回调水位平滑系数
+const LEVEL_SMOOTHING: f64 = 0.05;
+/// 附加时延硬上限，超出后直接丢弃积压回到目标水位
+const LATENCY_CEILING_MS: u32 = 60;
+/// 目标水位与上限之间的余量：回调前测得的水位会在目标附近上下浮动约一个块
+const CEILING_HEADROOM_MS: u32 = 10;
+
+const MIN_TARGET_LATENCY_MS: u32 = 10;
+const MAX_TARGET_LATENCY_MS: u32 = LATENCY_CEILING_MS - CEILING_HEADROOM_MS;
+
+/// 输出端自适应抖动缓冲。
+///
+/// 启动或欠载后先蓄到目标水位再出声；运行中按平滑水位微调重采样步长，
+/// 以分数重采样吸收两个设备时钟之间的漂移，而不是丢样本。
+pub struct JitterBuffer {
+    consumer: BridgeConsumer,
+    resampler: Resampler,
+    input_rate: f64,
+    target_ms: u32,
+    target: usize,
+    ceiling: usize,
+    avg_level: f64,
+    primed: bool,
+    pulled: Vec<f32>,
+    correction_ppm: Arc<AtomicI32>,
+}
+
+impl JitterBuffer {
+    pub fn new(
+        consumer: BridgeConsumer,
+        resampler: Resampler,
+        input_rate: u32,
+        target_latency_ms: u32,
+    ) -> Self {
+        let target_ms = target_latency_ms.clamp(MIN_TARGET_LATENCY_MS, MAX_TARGET_LATENCY_MS);
+        let frames = |ms: u32| (input_rate as u64 * ms as u64 / 1000) as usize;
+        let channels = consumer.channels();
+
+        Self {
+            consumer,
+            resampler,
+            input_rate: input_rate.max(1) as f64,
+            target_ms,
+            target: frames(target_ms),
+            ceiling: frames(LATENCY_CEILING_MS),
+            avg_level: 0.0,
+            primed: false,
+            pulled: Vec::with_capacity(input_rate as usize * channels),
+            correction_ppm: Arc::new(AtomicI32::new(0)),
+        }
+    }
+
+    /// 约束到允许范围后的目标时延
+    pub fn target_latency_ms(&self) -> u32 {
+        self.target_ms
+    }
+
+    /// 当前漂移修正量（ppm），供状态线程读取
+    pub fn correction_handle(&self) -> Arc<AtomicI32> {
+        self.correction_ppm.clone()
+    }
+
+    /// 按输出采样率生成交错样本填满 `out`，声道数与桥接缓冲一致
+    pub fn render(&mut self, out: &mut [f32]) {
+        let channels = self.consumer.channels();
+        let mut level = self.consumer.occupied();
+
+        if !self.primed {
+            if level < self.target {
+                out.fill(0.0);
+                return;
+            }
+            self.primed = true;
+            self.avg_level = level as f64;
+        }
+
+        if level > self.ceiling {
+            self.consumer.discard(level - self.target);
+            level = self.target;
+            self.avg_level = level as f64;
+        }
+
+        self.avg_level += (level as f64 - self.avg_level) * LEVEL_SMOOTHING;
+        let error_secs = (self.avg_level - self.target as f64) / self.input_rate;
+        let correction = (error_secs * ADJUST_GAIN).clamp(-MAX_ADJUST, MAX_ADJUST);
+        self.resampler.set_adjust(1.0 + correction);
+        self.correction_ppm
+            .store((correction * 1e6) as i32, Ordering::Relaxed);
+
+        let needed = self.resampler.input_needed(out.len() / channels);
+        self.pulled.resize(needed * channels, 0.0);
+        if self.consumer.pop(&mut self.pulled) < needed {
+            self.primed = false;
+        }
+        self.resampler.process(&self.pulled, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::bridge;
+
+    #[test]
+    fn level_should_converge_to_target_under_clock_drift() {
+        let rate = 48_000;
+        let (mut producer, consumer, monitor) = bridge(rate as usize, 1);
+        let mut jitter = JitterBuffer::new(consumer, Resampler::new(rate, rate, 1), rate, 30);
+        let correction = jitter.correction_handle();
+
+        // 输入时钟比输出快 300ppm，每 10ms 各回调一次
+        let input_per_tick = 480.0 * (1.0 + 300e-6);
+        let mut carry = 0.0;
+        let mut out = vec![0.0; 480];
+        for _ in 0..3000 {
+            carry += input_per_tick;
+            let n = carry as usize;
+            carry -= n as f64;
+            producer.push(&vec![0.1; n]);
+            jitter.render(&mut out);
+        }
+
+        // 水位在输出回调前测量，即输出端看到的时延
+        producer.push(&vec![0.1; 480]);
+        let level_ms = monitor.occupied() as f64 * 1000.0 / rate as f64;
+        assert!((level_ms - 30.0).abs() < 5.0, "level_ms = {level_ms}");
+        let ppm = correction.load(Ordering::Relaxed);
+        assert!((200..400).contains(&ppm), "ppm = {ppm}");
+        assert_eq!(monitor.overflows(), 0);
+    }
+
+    #[test]
+    fn backlog_above_ceiling_should_be_dropped() {
+        let rate = 48_000;
+        let (mut producer, consumer, monitor) = bridge(rate as usize, 1);
+        let mut jitter = JitterBuffer::new(consumer, Resampler::new(rate, rate, 1), rate, 20);
+
+        producer.push(&vec![0.0; 480 * 20]);
+        let mut out = vec![0.0; 480];
+        jitter.render(&mut out);
+
+        assert!(monitor.occupied() <= 20 * 48);
+        assert_eq!(monitor.overflows(), 1);
+    }
+
+    #[test]
+    fn latency_should_stay_under_ceiling_at_any_target() {
+        let rate = 44_100;
+        let (_producer, consumer, _monitor) = bridge(rate as usize, 1);
+        let jitter = JitterBuffer::new(consumer, Resampler::new(rate, rate, 1), rate, 1000);
+        assert_eq!(jitter.target_latency_ms(), MAX_TARGET_LATENCY_MS);
+        assert_eq!(jitter.target, 2205);
+        assert_eq!(jitter.ceiling, 2646);
+    }
+}
@@ -1,19 +1,58 @@
+#[cfg(any(target_os = "windows", test))]
+mod aec;
 mod app_state;
 mod audio;
+mod backoff;
+#[cfg(any(target_os = "windows", test))]
+mod bridge;
+#[cfg(any(target_os = "windows", test))]
+mod channels;
 mod commands;
 mod config;
+mod device_identity;
+mod device_watcher;
 mod driver_installer;
+mod dsp;
 mod error;
+mod failover;
 mod gate;
 mod hotkey;
+#[cfg(any(target_os = "windows", test))]
+mod input_switch;
+#[cfg(any(target_os = "windows", test))]
+mod jitter;
+mod meter;
 mod mouse_hook;
+#[cfg(any(target_os = "windows", test))]
+mod muted_speech;
+#[cfg(any(target_os = "windows", test))]
+mod preroll;
+#[cfg(any(target_os = "windows", test))]
+mod resampler;
+#[cfg(any(target_os = "windows", test))]
+mod sample_format;
 mod tray;
 mod types;
+#[cfg(any(target_os = "windows", test))]
+mod vad;
 mod virtual_mic;
+#[cfg(any(target_os = "windows", test))]
+mod vox;
+
+use std::{sync::mpsc, time::Duration};
 
 use tauri::{Emitter, Manager};
 
 use app_state::AppState;
+use device_watcher::DeviceWatcher;
+use types::DeviceList;
+
+/// `audio_levels` 事件的推送间隔，约 30Hz
+const LEVEL_EVENT_INTERVAL: Duration = Duration::from_millis(33);
+/// 轮询设备概况的间隔
+const DEVICE_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+/// 运行中检查引擎健康状况、执行故障转移的间隔
+const FAILOVER_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 
 pub fn run() {
     tauri::Builder::default()
@@ -57,6 +96,115 @@ pub fn run() {
                     log::warn!("启动自动初始化失败: {e}");
                 }
             });
+
+            // 检测器判定翻转时由音频线程唤醒，在此加锁更新门控并通知前端
+            let detector_handle = app.handle().clone();
+            let detector_thread = std::thread::spawn(move || {
+                let gate = detector_handle.state::<AppState>().gate.clone();
+                loop {
+                    if gate.apply_detectors() {
+                        let _ = detector_handle.emit("gate_state_changed", gate.snapshot());
+                    }
+                    std::thread::park();
+                }
+            });
+            app.state::<AppState>()
+                .gate
+                .set_detector_applier(detector_thread.thread().clone());
+            // 注册前到达的判定没有唤醒过该线程，补一次
+            detector_thread.thread().unpark();
+
+            // 引擎运行时按固定频率推送电平与闭麦说话提醒，避免音频线程直接触发事件；
+            // 引擎未运行时挂起，由启动引擎唤醒
+            let levels_handle = app.handle().clone();
+            let levels_thread = std::thread::spawn(move || loop {
+                let state = levels_handle.state::<AppState>();
+                let Some(levels) = state.inner().audio_levels() else {
+                    std::thread::park();
+                    continue;
+                };
+                let _ = levels_handle.emit("audio_levels", levels);
+                if let Some(warning) = state.inner().take_muted_speech() {
+                    let _ = levels_handle.emit("speaking_while_muted", warning);
+                }
+                std::thread::sleep(LEVEL_EVENT_INTERVAL);
+            });
+            app.state::<AppState>()
+                .set_level_listener(levels_thread.thread().clone());
+            // 注册前已启动的引擎没有唤醒过该线程，补一次
+            levels_thread.thread().unpark();
+
+            // 轮询设备概况，有变化时才完整枚举：通知前端设备增减，并把设备列表交给故障转移线程
+            let (devices_tx, devices_rx) = mpsc::channel::<(DeviceList, bool)>();
+            let watcher_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                let mut watcher = DeviceWatcher::default();
+                loop {
+                    std::thread::sleep(DEVICE_WATCH_INTERVAL);
+                    let summary = match audio::device_summary() {
+                        Ok(summary) if watcher.summary_changed(&summary) => summary,
+                        Ok(_) => continue,
+                        Err(e) => {
+                            log::warn!("{e}");
+                            continue;
+                        }
+                    };
+                    let devices = match audio::list_devices() {
+                        Ok(devices) => devices,
+                        Err(e) => {
+                            log::warn!("枚举音频设备失败: {e}");
+                            continue;
+                        }
+                    };
+                    let change = watcher.update(summary, &devices);
+                    let devices_changed = change.is_some();
+                    if let Some(change) = change {
+                        log::info!(
+                            "音频设备变化: 新增 {} 个，移除 {} 个",
+                            change.added.len(),
+                            change.removed.len()
+                        );
+                        let _ = watcher_handle.emit("devices_changed", change);
+                    }
+                    if devices_tx.send((devices, devices_changed)).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            // 设备插拔或流出错后按优先级切换到可用设备、按退避重启引擎，并通知前端切换原因
+            let failover_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                let mut devices = None;
+                loop {
+                    let devices_changed = match devices_rx.recv_timeout(FAILOVER_CHECK_INTERVAL) {
+                        Ok((latest, changed)) => {
+                            devices = Some(latest);
+                            changed
+                        }
+                        Err(mpsc::RecvTimeoutError::Timeout) => false,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    };
+                    let Some(devices) = devices.as_ref() else {
+                        continue;
+                    };
+                    let state = failover_handle.state::<AppState>();
+                    match state.inner().supervise(devices, devices_changed) {
+                        Ok(changes) => {
+                            for change in changes {
+                                log::info!(
+                                    "设备已切换: {} -> {}（{:?}）",
+                                    change.from_device_id,
+                                    change.to_device_id,
+                                    change.reason
+                                );
+                                let _ = failover_handle.emit("route_changed", change);
+                            }
+                        }
+                        Err(e) => log::warn!("设备故障转移失败: {e}"),
+                    }
+                }
+            });
             tray::create_tray(app.handle()).map_err(|e| e.to_string())?;
 
             if cfg.launch_on_startup {
@@ -85,10 +233,14 @@ pub fn run() {
             commands::get_app_config,
             commands::save_audio_route,
             commands::set_hotkey,
+            commands::set_engine_config,
+            commands::set_dsp_chain,
+            commands::set_input_profile,
             commands::start_engine,
             commands::stop_engine,
             commands::set_mic_gate,
             commands::get_runtime_status,
+            commands::get_audio_levels,
             commands::get_virtual_mic_status,
             commands::set_launch_on_startup,
             commands::set_minimize_to_tray,
@@ -0,0 +1,141 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::types::{AudioLevels, LevelReading};
+
+/// RMS 积分时间常数（毫秒），接近常见音量表的响应速度
+#[cfg(any(target_os = "windows", test))]
+const RMS_WINDOW_MS: f32 = 300.0;
+/// 峰值回落速度（dB/秒）
+#[cfg(any(target_os = "windows", test))]
+const PEAK_FALL_DB_PER_SEC: f32 = 20.0;
+/// 电平下限（dBFS），静音时报告该值而不是负无穷
+pub const FLOOR_DB: f32 = -100.0;
+
+fn to_db(linear: f32) -> f32 {
+    (20.0 * linear.max(1e-9).log10()).max(FLOOR_DB)
+}
+
+/// 一个测量点的电平。峰值与 RMS 打包进同一个 u64 原子写入，读取端不会拿到撕裂的组合。
+#[derive(Default)]
+pub struct LevelCell(AtomicU64);
+
+impl LevelCell {
+    #[cfg(any(target_os = "windows", test))]
+    fn store(&self, peak: f32, rms: f32) {
+        let packed = (u64::from(peak.to_bits()) << 32) | u64::from(rms.to_bits());
+        self.0.store(packed, Ordering::Relaxed);
+    }
+
+    pub fn load(&self) -> LevelReading {
+        let packed = self.0.load(Ordering::Relaxed);
+        LevelReading {
+            peak_db: to_db(f32::from_bits((packed >> 32) as u32)),
+            rms_db: to_db(f32::from_bits(packed as u32)),
+        }
+    }
+}
+
+/// 门控前（采集与处理之后）与门控后（包络之后）的电平，以及待发出的闭麦说话提醒
+#[derive(Default)]
+pub struct LevelMonitor {
+    pub pre_gate: LevelCell,
+    pub post_gate: LevelCell,
+    muted_speech: AtomicBool,
+}
+
+impl LevelMonitor {
+    pub fn snapshot(&self) -> AudioLevels {
+        AudioLevels {
+            pre_gate: self.pre_gate.load(),
+            post_gate: self.post_gate.load(),
+        }
+    }
+
+    /// 音频线程标记一次闭麦说话
+    #[cfg(target_os = "windows")]
+    pub fn flag_muted_speech(&self) {
+        self.muted_speech.store(true, Ordering::Relaxed);
+    }
+
+    /// 取走待发出的提醒，每次标记只返回一次 true
+    pub fn take_muted_speech(&self) -> bool {
+        self.muted_speech.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// 音频回调内的电平计：峰值带回落，RMS 为指数滑动平均。
+#[cfg(any(target_os = "windows", test))]
+pub struct LevelMeter {
+    peak: f32,
+    mean_square: f32,
+    rms_coeff: f32,
+    /// 每个样本的峰值回落系数
+    peak_fall: f32,
+}
+
+#[cfg(any(target_os = "windows", test))]
+impl LevelMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        let rate = sample_rate.max(1) as f32;
+        Self {
+            peak: 0.0,
+            mean_square: 0.0,
+            rms_coeff: 1.0 - (-1000.0 / (RMS_WINDOW_MS * rate)).exp(),
+            peak_fall: 10.0_f32.powf(-PEAK_FALL_DB_PER_SEC / 20.0 / rate),
+        }
+    }
+
+    /// 当前 RMS（线性值）
+    pub fn rms(&self) -> f32 {
+        self.mean_square.sqrt()
+    }
+
+    /// 当前 RMS（dBFS）
+    #[cfg(target_os = "windows")]
+    pub fn rms_db(&self) -> f32 {
+        to_db(self.rms())
+    }
+
+    /// 计量一个块并把结果发布到 `cell`
+    pub fn process(&mut self, block: &[f32], cell: &LevelCell) {
+        let mut block_peak = 0.0_f32;
+        for &sample in block {
+            block_peak = block_peak.max(sample.abs());
+            self.mean_square += (sample * sample - self.mean_square) * self.rms_coeff;
+        }
+        self.peak = block_peak.max(self.peak * self.peak_fall.powi(block.len() as i32));
+        cell.store(self.peak, self.rms());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    #[test]
+    fn meter_should_report_peak_and_rms_of_sine() {
+        let cell = LevelCell::default();
+        assert_eq!(cell.load().rms_db, FLOOR_DB);
+
+        let mut meter = LevelMeter::new(RATE);
+        let sine: Vec<f32> = (0..RATE as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / RATE as f32).sin() * 0.5)
+            .collect();
+        for block in sine.chunks(480) {
+            meter.process(block, &cell);
+        }
+        let reading = cell.load();
+        assert!((reading.peak_db + 6.02).abs() < 0.1, "{reading:?}");
+        assert!((reading.rms_db + 9.03).abs() < 0.3, "{reading:?}");
+
+        // 静音后峰值按回落速度下降，而不是瞬间归零
+        for block in vec![0.0; RATE as usize / 2].chunks(480) {
+            meter.process(block, &cell);
+        }
+        let reading = cell.load();
+        assert!((reading.peak_db + 16.02).abs() < 0.2, "{reading:?}");
+        assert!(reading.rms_db < -15.0, "{reading:?}");
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{error::AppError, gate::GateController, types::GateMode};
+use crate::{error::AppError, gate::GateController, types::HotkeyConfig};
 
 pub fn is_mouse_accelerator(accelerator: &str) -> bool {
     accelerator
@@ -14,7 +14,7 @@ mod imp {
     use std::{
         sync::{mpsc, OnceLock},
         thread,
-        time::Duration,
+        time::{Duration, Instant},
     };
 
     use parking_lot::Mutex;
@@ -37,7 +37,11 @@ mod imp {
     };
 
     use crate::{
-        error::AppError, gate::GateController, mouse_hook::is_mouse_accelerator, types::GateMode,
+        error::AppError,
+        gate::GateController,
+        hotkey::{hybrid_release, release_gate},
+        mouse_hook::is_mouse_accelerator,
+        types::{GateMode, HotkeyConfig},
     };
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -63,7 +67,7 @@ mod imp {
         binding: MouseBinding,
         gate: std::sync::Arc<GateController>,
         app: tauri::AppHandle,
-        mode: GateMode,
+        hotkey: HotkeyConfig,
     }
 
     struct MouseHookWorker {
@@ -185,19 +189,46 @@ mod imp {
             if let Some(context) = guard.as_ref() {
                 if let Some((button, is_pressed)) = extract_button(w_param as u32, l_param) {
                     if button == context.binding.button && modifiers_match(&context.binding) {
-                        match context.mode {
+                        match context.hotkey.mode {
                             GateMode::Ptt => {
                                 if is_pressed {
                                     context.gate.set_open(true, "mouse_hotkey");
                                 } else {
-                                    context.gate.set_open(false, "mouse_hotkey");
+                                    release_gate(
+                                        &context.app,
+                                        &context.gate,
+                                        context.hotkey.hang_time_ms,
+                                        "mouse_hotkey",
+                                    );
                                 }
                             }
-                            GateMode::Toggle | GateMode::Hybrid => {
+                            GateMode::Toggle => {
                                 if is_pressed {
                                     context.gate.toggle("mouse_hotkey");
                                 }
                             }
+                            GateMode::PushToMute => {
+                                context.gate.set_open(!is_pressed, "mouse_hotkey");
+                            }
+                            GateMode::Vox | GateMode::Vad => {
+                                context.gate.override_vox(
+                                    &context.hotkey.vox_override,
+                                    is_pressed,
+                                    "mouse_hotkey",
+                                );
+                            }
+                            GateMode::Hybrid => {
+                                if is_pressed {
+                                    context.gate.hybrid_press(Instant::now(), "mouse_hotkey");
+                                } else {
+                                    hybrid_release(
+                                        &context.app,
+                                        &context.gate,
+                                        &context.hotkey,
+                                        "mouse_hotkey",
+                                    );
+                                }
+                            }
                         }
 
                         let _ = context
@@ -252,18 +283,17 @@ mod imp {
         pub fn register(
             &self,
             app: &tauri::AppHandle,
-            accelerator: &str,
+            config: &HotkeyConfig,
             gate: std::sync::Arc<GateController>,
-            mode: GateMode,
         ) -> Result<(), AppError> {
             self.unregister();
 
-            let binding = parse_mouse_binding(accelerator)?;
+            let binding = parse_mouse_binding(&config.accelerator)?;
             let context = HookContext {
                 binding,
                 gate,
                 app: app.clone(),
-                mode,
+                hotkey: config.clone(),
             };
 
             let (ready_tx, ready_rx) = mpsc::sync_channel::<Result<u32, String>>(1);
@@ -322,9 +352,8 @@ impl MouseHookManager {
     pub fn register(
         &self,
         _app: &tauri::AppHandle,
-        _accelerator: &str,
+        _config: &HotkeyConfig,
         _gate: Arc<GateController>,
-        _mode: GateMode,
     ) -> Result<(), AppError> {
         Err(AppError::Hotkey("鼠标全局快捷键仅支持 Windows".to_string()))
     }
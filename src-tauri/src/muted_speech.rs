@@ -0,0 +1,126 @@
+use crate::types::MutedSpeechConfig;
+
+/// 说话中允许的停顿（毫秒），不超过该时长的停顿不打断累计
+const GAP_TOLERANCE_MS: u32 = 400;
+
+/// 闭麦说话检测：门控关闭时输入电平持续高于门限即提醒，两次提醒之间有冷却时间。
+pub struct MutedSpeechDetector {
+    enabled: bool,
+    threshold_db: f32,
+    min_speech: u64,
+    gap_tolerance: u64,
+    cooldown: u64,
+    /// 本轮累计的说话样本数
+    speech: u64,
+    /// 当前连续停顿的样本数
+    quiet: u64,
+    since_warning: u64,
+}
+
+impl MutedSpeechDetector {
+    pub fn new(config: &MutedSpeechConfig, sample_rate: u32) -> Self {
+        let samples = |ms: u64| ms * u64::from(sample_rate) / 1000;
+        let cooldown = samples(u64::from(config.cooldown_secs) * 1000);
+        Self {
+            enabled: config.enabled,
+            threshold_db: config.threshold_db,
+            min_speech: samples(u64::from(config.min_speech_ms)).max(1),
+            gap_tolerance: samples(u64::from(GAP_TOLERANCE_MS)),
+            cooldown,
+            speech: 0,
+            quiet: 0,
+            // 启动后首次检测到即可提醒
+            since_warning: cooldown,
+        }
+    }
+
+    /// 每个输入块调用一次，`level_db` 为当前输入电平；需要提醒时返回 true
+    pub fn process(&mut self, block_len: usize, level_db: f32, gate_open: bool) -> bool {
+        let len = block_len as u64;
+        self.since_warning = self.since_warning.saturating_add(len);
+        if !self.enabled || gate_open {
+            self.speech = 0;
+            self.quiet = 0;
+            return false;
+        }
+
+        if level_db >= self.threshold_db {
+            self.speech += len;
+            self.quiet = 0;
+        } else {
+            self.quiet += len;
+            if self.quiet > self.gap_tolerance {
+                self.speech = 0;
+            }
+        }
+
+        if self.speech < self.min_speech || self.since_warning < self.cooldown {
+            return false;
+        }
+        self.speech = 0;
+        self.since_warning = 0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+    const BLOCK: usize = 480;
+
+    fn config() -> MutedSpeechConfig {
+        MutedSpeechConfig {
+            enabled: true,
+            threshold_db: -40.0,
+            min_speech_ms: 1000,
+            cooldown_secs: 10,
+        }
+    }
+
+    /// 以 10ms 块喂入 `ms` 毫秒的固定电平，返回提醒次数
+    fn feed(
+        detector: &mut MutedSpeechDetector,
+        ms: usize,
+        level_db: f32,
+        gate_open: bool,
+    ) -> usize {
+        (0..ms / 10)
+            .filter(|_| detector.process(BLOCK, level_db, gate_open))
+            .count()
+    }
+
+    #[test]
+    fn sustained_speech_while_muted_should_warn_once_per_cooldown() {
+        let mut detector = MutedSpeechDetector::new(&config(), RATE);
+        assert_eq!(feed(&mut detector, 900, -30.0, false), 0);
+        // 短暂停顿不打断累计
+        assert_eq!(feed(&mut detector, 200, -70.0, false), 0);
+        assert_eq!(feed(&mut detector, 200, -30.0, false), 1);
+        // 冷却期内持续说话不再重复提醒
+        assert_eq!(feed(&mut detector, 8000, -30.0, false), 0);
+        assert_eq!(feed(&mut detector, 3000, -30.0, false), 1);
+    }
+
+    #[test]
+    fn open_gate_quiet_input_or_disabled_should_not_warn() {
+        let mut detector = MutedSpeechDetector::new(&config(), RATE);
+        assert_eq!(feed(&mut detector, 5000, -30.0, true), 0);
+        assert_eq!(feed(&mut detector, 5000, -50.0, false), 0);
+        // 断续的咳嗽、敲击不会累积成提醒
+        for _ in 0..20 {
+            assert_eq!(feed(&mut detector, 300, -30.0, false), 0);
+            assert_eq!(feed(&mut detector, 600, -70.0, false), 0);
+        }
+
+        let mut disabled = MutedSpeechDetector::new(
+            &MutedSpeechConfig {
+                enabled: false,
+                ..config()
+            },
+            RATE,
+        );
+        assert_eq!(feed(&mut disabled, 5000, -30.0, false), 0);
+    }
+}
@@ -0,0 +1,323 @@
+/// 最短/最长基音周期（毫秒），时间压缩按基音周期跳跃以保持音高
+const MIN_PERIOD_MS: f32 = 2.5;
+const MAX_PERIOD_MS: f32 = 15.0;
+/// 跳跃时的交叉淡化上限（毫秒）
+const MAX_FADE_MS: f32 = 5.0;
+/// 相关性计算窗口（毫秒）
+const CORR_WINDOW_MS: f32 = 5.0;
+const MAX_PREROLL_MS: u32 = 500;
+
+/// 开麦预录回溯。
+///
+/// 持续保留最近一段门控前的音频；闭麦→开麦时把播放位置回退到预录起点，
+/// 随后按基音周期跳跃（带交叉淡化）做时间压缩，逐步追回实时位置。
+/// 每次跳跃后至少正常播放两个周期，压缩倍率不超过 1.5x。
+/// 多声道时以帧为单位跳跃，基音周期按各声道之和估计，声道之间保持同步。
+pub struct PreRoll {
+    channels: usize,
+    /// 交错存放的历史帧
+    history: Vec<f32>,
+    /// 历史容量（帧数）
+    capacity: usize,
+    /// 已写入的帧总数
+    head: u64,
+    /// 下一个输出帧对应的历史位置
+    cursor: u64,
+    preroll: u64,
+    min_period: usize,
+    max_period: usize,
+    max_fade: usize,
+    corr_window: usize,
+    was_open: bool,
+    jump: usize,
+    fade_len: usize,
+    fade_remaining: usize,
+    cooldown: usize,
+}
+
+impl PreRoll {
+    pub fn new(preroll_ms: u32, sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let per_ms = sample_rate as f32 / 1000.0;
+        let preroll = (preroll_ms.min(MAX_PREROLL_MS) as f32 * per_ms) as usize;
+        // 额外预留 1 秒，覆盖任意合理的回调块长度
+        let capacity = preroll + sample_rate as usize + 1;
+
+        Self {
+            channels,
+            history: vec![0.0; capacity * channels],
+            capacity,
+            head: 0,
+            cursor: 0,
+            preroll: preroll as u64,
+            min_period: ((MIN_PERIOD_MS * per_ms) as usize).max(1),
+            max_period: ((MAX_PERIOD_MS * per_ms) as usize).max(2),
+            max_fade: ((MAX_FADE_MS * per_ms) as usize).max(1),
+            corr_window: ((CORR_WINDOW_MS * per_ms) as usize).max(1),
+            was_open: false,
+            jump: 0,
+            fade_len: 0,
+            fade_remaining: 0,
+            cooldown: 0,
+        }
+    }
+
+    /// 当前落后实时位置的帧数
+    pub fn lag(&self) -> u64 {
+        self.head - self.cursor
+    }
+
+    /// 记录实时块，并在需要时把交错的 `block` 替换为回溯/压缩后的音频
+    pub fn process(&mut self, open: bool, block: &mut [f32]) {
+        // 超长块分段处理，保证每段写入后预录起点仍在历史中
+        let max_frames = self.capacity - self.preroll as usize;
+        for chunk in block.chunks_mut(max_frames * self.channels) {
+            self.process_chunk(open, chunk);
+        }
+    }
+
+    fn process_chunk(&mut self, open: bool, block: &mut [f32]) {
+        let channels = self.channels;
+        let block_start = self.head;
+        let frames = (block.len() / channels) as u64;
+        for frame in block.chunks_exact(channels) {
+            let at = self.slot(self.head);
+            self.history[at..at + channels].copy_from_slice(frame);
+            self.head += 1;
+        }
+
+        if self.preroll > 0 && open && !self.was_open {
+            let available = block_start.min((self.capacity as u64).saturating_sub(frames));
+            self.cursor = block_start - self.preroll.min(available);
+            self.fade_remaining = 0;
+            self.cooldown = 0;
+        }
+        self.was_open = open;
+
+        if self.cursor >= block_start {
+            self.cursor = self.head;
+            return;
+        }
+
+        for (i, out) in block.chunks_exact_mut(channels).enumerate() {
+            let live = block_start + i as u64;
+
+            if self.fade_remaining > 0 {
+                let w = 1.0 - self.fade_remaining as f32 / (self.fade_len + 1) as f32;
+                let a = self.slot(self.cursor);
+                let b = self.slot(self.cursor + self.jump as u64);
+                for (c, sample) in out.iter_mut().enumerate() {
+                    let (a, b) = (self.history[a + c], self.history[b + c]);
+                    *sample = a + (b - a) * w;
+                }
+                self.cursor += 1;
+                self.fade_remaining -= 1;
+                if self.fade_remaining == 0 {
+                    self.cursor += self.jump as u64;
+                }
+                continue;
+            }
+
+            let at = self.slot(self.cursor);
+            out.copy_from_slice(&self.history[at..at + channels]);
+            self.cursor += 1;
+            self.cooldown = self.cooldown.saturating_sub(1);
+
+            let behind = (live + 1).saturating_sub(self.cursor) as usize;
+            if behind == 0 || self.cooldown > 0 {
+                continue;
+            }
+            self.plan_jump(behind, (self.head - self.cursor) as usize);
+        }
+    }
+
+    /// 规划下一次跳跃；`behind` 为落后实时的帧数，`readable` 为游标之后已写入的帧数
+    fn plan_jump(&mut self, behind: usize, readable: usize) {
+        let fade = self.max_fade.min(self.min_period);
+        let max_period = self
+            .max_period
+            .min(behind)
+            .min(readable.saturating_sub(self.corr_window.max(fade)));
+
+        let period = if max_period >= self.min_period {
+            self.best_period(max_period)
+        } else if behind < self.min_period && behind + fade <= readable {
+            // 剩余落后量不足一个周期：以短淡化直接对齐实时位置
+            behind
+        } else {
+            return;
+        };
+
+        self.jump = period;
+        self.fade_len = fade.min(period);
+        self.fade_remaining = self.fade_len;
+        self.cooldown = period * 2;
+    }
+
+    /// 在 [min_period, max_period] 内寻找与当前波形最相似的周期（粗搜 + 细化）
+    fn best_period(&self, max_period: usize) -> usize {
+        let score = |period: usize, stride: usize| {
+            let mut dot = 0.0_f32;
+            let mut energy = 0.0_f32;
+            for k in (0..self.corr_window).step_by(stride) {
+                let a = self.sample(self.cursor + k as u64);
+                let b = self.sample(self.cursor + (period + k) as u64);
+                dot += a * b;
+                energy += a * a + b * b;
+            }
+            if energy <= f32::EPSILON {
+                0.0
+            } else {
+                2.0 * dot / energy
+            }
+        };
+
+        let mut best = (self.min_period, f32::MIN);
+        for period in (self.min_period..=max_period).step_by(4) {
+            let s = score(period, 4);
+            if s > best.1 {
+                best = (period, s);
+            }
+        }
+
+        let coarse = best.0;
+        let lo = coarse.saturating_sub(3).max(self.min_period);
+        let hi = (coarse + 3).min(max_period);
+        best = (coarse, f32::MIN);
+        for period in lo..=hi {
+            let s = score(period, 1);
+            if s > best.1 {
+                best = (period, s);
+            }
+        }
+        best.0
+    }
+
+    /// 第 `index` 帧在历史缓冲中的起始下标
+    fn slot(&self, index: u64) -> usize {
+        (index % self.capacity as u64) as usize * self.channels
+    }
+
+    /// 第 `index` 帧各声道之和，用于基音周期估计
+    fn sample(&self, index: u64) -> f32 {
+        let at = self.slot(index);
+        self.history[at..at + self.channels].iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(start: usize, len: usize) -> Vec<f32> {
+        (start..start + len)
+            .map(|i| (2.0 * std::f32::consts::PI * 180.0 * i as f32 / 48_000.0).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn opening_should_replay_preroll_then_catch_up() {
+        let rate = 48_000;
+        let mut preroll = PreRoll::new(200, rate, 1);
+        let block = 480;
+        let mut t = 0;
+
+        for _ in 0..50 {
+            let mut live = tone(t, block);
+            preroll.process(false, &mut live);
+            t += block;
+        }
+
+        let mut first = tone(t, block);
+        preroll.process(true, &mut first);
+        // 开麦首个样本即为 200ms 之前的音频
+        let expected = tone(t - 9600, block);
+        assert!((first[0] - expected[0]).abs() < 1e-6);
+        assert!(first
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 0.02));
+        t += block;
+
+        let mut output = first;
+        let mut caught_up_at = None;
+        for n in 0..200 {
+            let mut live = tone(t, block);
+            let reference = live.clone();
+            preroll.process(true, &mut live);
+            t += block;
+            output.extend_from_slice(&live);
+            match caught_up_at {
+                // 追回之后应与实时音频逐样本一致
+                Some(_) => assert_eq!(live, reference),
+                None if preroll.lag() == 0 => caught_up_at = Some(n),
+                None => {}
+            }
+        }
+
+        // 200ms 回溯在 1.5x 上限下至少需要 400ms，且应在 2 秒内追回
+        let caught_up_at = caught_up_at.expect("preroll never caught up");
+        assert!(
+            (40..200).contains(&caught_up_at),
+            "caught_up_at = {caught_up_at}"
+        );
+
+        // 基音对齐跳跃后波形应保持连续
+        let max_step = 2.0 * std::f32::consts::PI * 180.0 / 48_000.0 * 0.5;
+        let max_jump = output
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0_f32, f32::max);
+        assert!(max_jump < max_step * 2.0, "max_jump = {max_jump}");
+    }
+
+    #[test]
+    fn oversized_block_should_be_processed_in_chunks() {
+        let rate = 48_000;
+        let mut preroll = PreRoll::new(200, rate, 1);
+        let mut t = 0;
+        for _ in 0..50 {
+            preroll.process(false, &mut tone(t, 480));
+            t += 480;
+        }
+
+        // 单块超过历史容量（预录 + 1 秒）
+        let len = 2 * rate as usize;
+        let mut block = tone(t, len);
+        preroll.process(true, &mut block);
+        let expected = tone(t - 9600, 1);
+        assert!((block[0] - expected[0]).abs() < 1e-6);
+        assert!(preroll.lag() < 9600, "lag = {}", preroll.lag());
+    }
+
+    #[test]
+    fn stereo_preroll_should_keep_channels_in_sync() {
+        let mut preroll = PreRoll::new(200, 48_000, 2);
+        let stereo = |start: usize| -> Vec<f32> {
+            tone(start, 480).iter().flat_map(|s| [*s, -*s]).collect()
+        };
+        let mut t = 0;
+        for _ in 0..50 {
+            preroll.process(false, &mut stereo(t));
+            t += 480;
+        }
+        for _ in 0..100 {
+            let mut block = stereo(t);
+            preroll.process(true, &mut block);
+            t += 480;
+            assert!(block.chunks_exact(2).all(|f| f[0] == -f[1]));
+        }
+        assert_eq!(preroll.lag(), 0);
+    }
+
+    #[test]
+    fn disabled_preroll_should_pass_through() {
+        let mut preroll = PreRoll::new(0, 48_000, 1);
+        let mut block = tone(0, 480);
+        let reference = block.clone();
+        preroll.process(false, &mut block);
+        preroll.process(true, &mut block);
+        assert_eq!(block, reference);
+        assert_eq!(preroll.lag(), 0);
+    }
+}
@@ -0,0 +1,276 @@
+use std::f64::consts::PI;
+
+/// 插值窗口长度（输入样本数），左右各一半
+const TAPS: usize = 32;
+/// 多相表相位数，相位之间线性插值
+const PHASES: usize = 256;
+
+/// 流式加窗 sinc 重采样器（多相表实现），处理交错多声道数据。
+///
+/// 由输出端驱动：先用 [`Resampler::input_needed`] 询问本次需要多少输入帧，
+/// 再调用 [`Resampler::process`] 生成固定数量的输出帧。
+pub struct Resampler {
+    channels: usize,
+    /// 每个输出样本前进的输入样本数（in_rate / out_rate）
+    step: f64,
+    /// 步长微调系数，供时钟漂移补偿使用
+    adjust: f64,
+    frac: f64,
+    history: Vec<f32>,
+    buf: Vec<f32>,
+    table: Vec<f32>,
+}
+
+impl Resampler {
+    pub fn new(in_rate: u32, out_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let in_rate = in_rate.max(1) as f64;
+        let out_rate = out_rate.max(1) as f64;
+        let step = in_rate / out_rate;
+        // 降采样时把截止频率压到输出奈奎斯特以下，抑制混叠
+        let cutoff = if step > 1.0 { 0.97 / step } else { 1.0 };
+
+        Self {
+            channels,
+            step,
+            adjust: 1.0,
+            frac: 0.0,
+            history: vec![0.0; TAPS * channels],
+            buf: Vec::with_capacity(TAPS * 4 * channels),
+            table: build_table(cutoff),
+        }
+    }
+
+    /// 名义转换比（输出采样率 / 输入采样率）
+    pub fn ratio(&self) -> f64 {
+        1.0 / self.step
+    }
+
+    /// 设置步长微调系数，大于 1 时消费输入更快
+    pub fn set_adjust(&mut self, adjust: f64) {
+        self.adjust = adjust;
+    }
+
+    /// 生成 `out_frames` 个输出帧需要消费的输入帧数
+    pub fn input_needed(&self, out_frames: usize) -> usize {
+        (self.frac + out_frames as f64 * self.effective_step()).floor() as usize
+    }
+
+    /// 消费 `input`（帧数必须等于 `input_needed` 对应 `out` 的帧数）并填满 `out`
+    pub fn process(&mut self, input: &[f32], out: &mut [f32]) {
+        let step = self.effective_step();
+        let channels = self.channels;
+        let out_frames = out.len() / channels;
+        debug_assert_eq!(input.len(), self.input_needed(out_frames) * channels);
+
+        self.buf.clear();
+        self.buf.extend_from_slice(&self.history);
+        self.buf.extend_from_slice(input);
+
+        let mut pos = self.frac;
+        for frame in out.chunks_exact_mut(channels) {
+            let start = pos as usize;
+            let phase = (pos - start as f64) * PHASES as f64;
+            let p = (phase as usize).min(PHASES - 1);
+            let mix = (phase - p as f64) as f32;
+
+            let window = &self.buf[start * channels..(start + TAPS) * channels];
+            let lo = &self.table[p * TAPS..(p + 1) * TAPS];
+            let hi = &self.table[(p + 1) * TAPS..(p + 2) * TAPS];
+            for (c, sample) in frame.iter_mut().enumerate() {
+                let mut acc = 0.0_f32;
+                for ((x, a), b) in window.iter().skip(c).step_by(channels).zip(lo).zip(hi) {
+                    acc += x * (a + (b - a) * mix);
+                }
+                *sample = acc;
+            }
+            pos += step;
+        }
+
+        let advance = input.len() / channels;
+        self.frac = (self.frac + out_frames as f64 * step) - advance as f64;
+        self.history
+            .copy_from_slice(&self.buf[advance * channels..(advance + TAPS) * channels]);
+    }
+
+    /// 现有 `in_frames` 个输入帧最多能生成的输出帧数
+    pub fn output_available(&self, in_frames: usize) -> usize {
+        let step = self.effective_step();
+        let mut frames = ((in_frames as f64 + 1.0 - self.frac) / step)
+            .ceil()
+            .max(1.0) as usize
+            - 1;
+        while frames > 0 && self.input_needed(frames) > in_frames {
+            frames -= 1;
+        }
+        frames
+    }
+
+    fn effective_step(&self) -> f64 {
+        self.step * self.adjust
+    }
+}
+
+/// 由输入端驱动的重采样：每次送入任意帧数，输出当前能生成的全部帧，余下的输入留到下次。
+///
+/// 用于把切换进来的输入设备转换到处理链的采样率。
+pub struct PushResampler {
+    inner: Resampler,
+    pending: Vec<f32>,
+}
+
+impl PushResampler {
+    pub fn new(in_rate: u32, out_rate: u32, channels: usize) -> Self {
+        Self {
+            inner: Resampler::new(in_rate, out_rate, channels),
+            pending: Vec::with_capacity(in_rate as usize * channels.max(1)),
+        }
+    }
+
+    /// 送入交错的 `input`，把能生成的输出帧写入 `out`（覆盖原有内容）
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        let channels = self.inner.channels;
+        self.pending.extend_from_slice(input);
+        let frames = self.inner.output_available(self.pending.len() / channels);
+        let consumed = self.inner.input_needed(frames) * channels;
+        out.clear();
+        out.resize(frames * channels, 0.0);
+        self.inner.process(&self.pending[..consumed], out);
+        self.pending.drain(..consumed);
+    }
+}
+
+/// 生成 (PHASES + 1) 组、每组 TAPS 个系数的多相表，每组归一化为单位直流增益
+fn build_table(cutoff: f64) -> Vec<f32> {
+    let half = (TAPS / 2) as f64;
+    let mut table = Vec::with_capacity((PHASES + 1) * TAPS);
+
+    for p in 0..=PHASES {
+        let frac = p as f64 / PHASES as f64;
+        let mut row = [0.0_f64; TAPS];
+        for (j, coeff) in row.iter_mut().enumerate() {
+            let x = j as f64 - (half - 1.0) - frac;
+            let sinc = if x.abs() < 1e-9 {
+                1.0
+            } else {
+                (PI * cutoff * x).sin() / (PI * x)
+            };
+            // Blackman 窗，窗口中心与插值点对齐
+            let n = (x + half) / TAPS as f64;
+            let window = if (0.0..=1.0).contains(&n) {
+                0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos()
+            } else {
+                0.0
+            };
+            *coeff = sinc * window;
+        }
+
+        let sum: f64 = row.iter().sum();
+        table.extend(row.iter().map(|c| (c / sum) as f32));
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(resampler: &mut Resampler, input: &[f32], block: usize, blocks: usize) -> Vec<f32> {
+        let mut cursor = 0;
+        let mut output = Vec::new();
+        for _ in 0..blocks {
+            let need = resampler.input_needed(block);
+            let mut out = vec![0.0; block];
+            resampler.process(&input[cursor..cursor + need], &mut out);
+            cursor += need;
+            output.extend_from_slice(&out);
+        }
+        output
+    }
+
+    #[test]
+    fn equal_rates_should_pass_through_with_fixed_delay() {
+        let mut resampler = Resampler::new(48_000, 48_000, 1);
+        let input: Vec<f32> = (0..4096).map(|i| ((i * 37) % 101) as f32 / 101.0).collect();
+        let output = run(&mut resampler, &input, 480, 8);
+
+        let delay = TAPS / 2 + 1;
+        for (i, sample) in output.iter().enumerate().skip(delay) {
+            assert!((sample - input[i - delay]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn interleaved_channels_should_stay_separate() {
+        let mut resampler = Resampler::new(48_000, 48_000, 2);
+        let input: Vec<f32> = (0..4096)
+            .map(|i| {
+                if i % 2 == 0 {
+                    (i / 2) as f32 / 2048.0
+                } else {
+                    -0.5
+                }
+            })
+            .collect();
+        let mut output = Vec::new();
+        let mut cursor = 0;
+        for _ in 0..8 {
+            let need = resampler.input_needed(240) * 2;
+            let mut out = vec![0.0; 480];
+            resampler.process(&input[cursor..cursor + need], &mut out);
+            cursor += need;
+            output.extend_from_slice(&out);
+        }
+
+        let delay = TAPS / 2 + 1;
+        for (k, frame) in output.chunks_exact(2).enumerate().skip(delay) {
+            assert!((frame[0] - input[(k - delay) * 2]).abs() < 1e-5);
+            assert!((frame[1] + 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn push_resampler_should_convert_arbitrary_blocks() {
+        let mut resampler = PushResampler::new(44_100, 48_000, 2);
+        let mut out = Vec::new();
+        let mut produced = 0;
+        // 不规则的块长，累计输出帧数应跟随采样率之比
+        for i in 0..100 {
+            let frames = 300 + (i * 37) % 200;
+            resampler.process(&vec![0.25; frames * 2], &mut out);
+            assert_eq!(out.len() % 2, 0);
+            produced += out.len() / 2;
+        }
+        let consumed: usize = (0..100).map(|i| 300 + (i * 37) % 200).sum();
+        let expected = consumed as f64 * 48_000.0 / 44_100.0;
+        assert!(
+            (produced as f64 - expected).abs() < 2.0,
+            "{produced} vs {expected}"
+        );
+        assert!(out.iter().all(|s| (s - 0.25).abs() < 1e-3));
+    }
+
+    #[test]
+    fn upsampling_should_preserve_tone_frequency() {
+        let (in_rate, out_rate) = (44_100_u32, 48_000_u32);
+        let tone = 1000.0_f64;
+        let input: Vec<f32> = (0..44_100)
+            .map(|i| (2.0 * PI * tone * i as f64 / in_rate as f64).sin() as f32)
+            .collect();
+
+        let mut resampler = Resampler::new(in_rate, out_rate, 1);
+        assert!((resampler.ratio() - 48_000.0 / 44_100.0).abs() < 1e-9);
+        let output = run(&mut resampler, &input, 480, 80);
+
+        // 跳过起始瞬态后与理想延迟正弦比较
+        let delay = (TAPS / 2 + 1) as f64;
+        let mut max_err = 0.0_f64;
+        for (k, sample) in output.iter().enumerate().skip(200) {
+            let t = k as f64 * in_rate as f64 / out_rate as f64 - delay;
+            let expected = (2.0 * PI * tone * t / in_rate as f64).sin();
+            max_err = max_err.max((*sample as f64 - expected).abs());
+        }
+        assert!(max_err < 1e-2, "max_err = {max_err}");
+    }
+}
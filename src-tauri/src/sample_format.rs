@@ -0,0 +1,80 @@
+use cpal::SampleFormat;
+
+/// 采样格式协商优先级，数值越大越优先；`None` 表示引擎不支持该格式
+pub fn format_rank(format: SampleFormat) -> Option<u8> {
+    match format {
+        SampleFormat::F32 => Some(6),
+        SampleFormat::I32 => Some(5),
+        SampleFormat::F64 => Some(4),
+        SampleFormat::I16 => Some(3),
+        SampleFormat::U16 => Some(2),
+        SampleFormat::I8 => Some(1),
+        SampleFormat::U8 => Some(0),
+        _ => None,
+    }
+}
+
+/// 整数格式的有效位深，浮点格式返回 `None`
+pub fn integer_bits(format: SampleFormat) -> Option<u32> {
+    if format.is_float() {
+        return None;
+    }
+    Some(format.sample_size() as u32 * 8)
+}
+
+/// 写入整数输出前使用的 TPDF 抖动，幅度为 ±1 LSB。
+pub struct TpdfDither {
+    lsb: f32,
+    seed: u32,
+}
+
+impl TpdfDither {
+    pub fn new(bits: u32) -> Self {
+        Self {
+            lsb: 2.0_f32.powi(1 - bits as i32),
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    pub fn apply(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            let noise = (self.next_uniform() - self.next_uniform()) * self.lsb;
+            *sample = (*sample + noise).clamp(-1.0, 1.0);
+        }
+    }
+
+    fn next_uniform(&mut self) -> f32 {
+        // xorshift32，实时线程内无需加锁
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_should_outrank_integer_formats() {
+        let mut formats = [SampleFormat::U16, SampleFormat::F32, SampleFormat::I16];
+        formats.sort_by_key(|f| std::cmp::Reverse(format_rank(*f)));
+        assert_eq!(formats[0], SampleFormat::F32);
+        assert_eq!(integer_bits(SampleFormat::I16), Some(16));
+        assert_eq!(integer_bits(SampleFormat::F32), None);
+    }
+
+    #[test]
+    fn dither_should_stay_within_one_lsb() {
+        let mut dither = TpdfDither::new(16);
+        let mut samples = vec![0.25_f32; 4096];
+        dither.apply(&mut samples);
+
+        let lsb = 1.0 / 32_768.0;
+        assert!(samples.iter().all(|s| (s - 0.25).abs() <= lsb));
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!((mean - 0.25).abs() < lsb * 0.1);
+        assert!(samples.iter().any(|s| *s != 0.25));
+    }
+}
@@ -7,7 +7,7 @@ use tauri::{
 use crate::{
     app_state::AppState,
     error::AppError,
-    types::{EngineState, RuntimeStatus},
+    types::{EngineState, GateMode, RuntimeStatus},
 };
 
 pub fn create_tray(app: &tauri::AppHandle) -> Result<(), AppError> {
@@ -41,8 +41,19 @@ fn build_menu(
         EngineState::Error => "语音链路：错误",
     };
 
+    let mode_text = match status.gate_state.mode {
+        GateMode::Ptt => "按键模式：按住说话",
+        GateMode::Toggle => "按键模式：切换开关",
+        GateMode::Hybrid => "按键模式：混合模式",
+        GateMode::PushToMute => "按键模式：按键静音",
+        GateMode::Vox => "按键模式：声控",
+        GateMode::Vad => "按键模式：语音检测",
+    };
+
     let engine_status = MenuItem::with_id(app, "engine_status", state_text, false, None::<&str>)
         .map_err(|e| AppError::System(format!("创建菜单失败: {e}")))?;
+    let gate_mode = MenuItem::with_id(app, "gate_mode", mode_text, false, None::<&str>)
+        .map_err(|e| AppError::System(format!("创建菜单失败: {e}")))?;
 
     let show_main = MenuItem::with_id(app, "show_main", "显示主界面", true, None::<&str>)
         .map_err(|e| AppError::System(format!("创建菜单失败: {e}")))?;
@@ -51,6 +62,7 @@ fn build_menu(
 
     MenuBuilder::new(app)
         .item(&engine_status)
+        .item(&gate_mode)
         .separator()
         .item(&show_main)
         .separator()
@@ -78,6 +90,11 @@ fn handle_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
         _ => {}
     }
 
+    refresh_tray(app);
+}
+
+/// 按最新运行状态重建托盘菜单
+pub fn refresh_tray(app: &tauri::AppHandle) {
     let state = app.state::<AppState>();
     let latest = state.inner().runtime_status();
     if let Ok(menu) = build_menu(app, &latest) {
@@ -1,13 +1,40 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+use crate::meter::FLOOR_DB;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum DeviceDirection {
     Input,
     Output,
 }
 
+/// 设备支持的一种流格式摘要
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ConfigSignature {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+}
+
+/// 设备指纹：名称、音频后端、支持的格式，以及系统端点 ID（可用时）。
+///
+/// 设备序号会随插拔变化，指纹用于生成稳定的设备 id，并在 id 失效时重新匹配设备。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceFingerprint {
+    pub direction: DeviceDirection,
+    pub name: String,
+    pub host: String,
+    #[serde(default)]
+    pub endpoint_id: Option<String>,
+    #[serde(default)]
+    pub configs: Vec<ConfigSignature>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceInfo {
     pub id: String,
@@ -15,6 +42,9 @@ pub struct DeviceInfo {
     pub direction: DeviceDirection,
     pub is_default: bool,
     pub is_virtual_candidate: bool,
+    /// 默认格式下的声道数，读取失败时为 0
+    pub channels: u16,
+    pub fingerprint: DeviceFingerprint,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -23,24 +53,55 @@ pub struct DeviceList {
     pub outputs: Vec<DeviceInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// 设备热插拔后发出的 `devices_changed` 事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicesChanged {
+    pub added: Vec<DeviceInfo>,
+    pub removed: Vec<DeviceInfo>,
+    /// 变化后的完整设备列表
+    pub devices: DeviceList,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum GateMode {
+    #[default]
     Ptt,
     Toggle,
     Hybrid,
+    /// 按键静音：默认开麦，按住期间闭麦
+    #[serde(rename = "push_to_mute")]
+    PushToMute,
+    /// 声控：由输入电平自动开闭麦
+    Vox,
+    /// 语音检测：由频谱语音活动检测自动开闭麦
+    Vad,
 }
 
-impl Default for GateMode {
-    fn default() -> Self {
-        Self::Ptt
-    }
+/// 声控模式下快捷键的覆盖行为
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VoxOverride {
+    /// 快捷键不影响声控
+    Off,
+    /// 按住期间强制闭麦
+    #[default]
+    Mute,
+    /// 按住期间强制开麦
+    Talk,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HotkeyConfig {
     pub accelerator: String,
     pub mode: GateMode,
+    /// 按住说话松开后继续保持开麦的时长（毫秒），0 表示立即闭麦
+    pub hang_time_ms: u32,
+    /// 混合模式下短按与长按的分界（毫秒），短于该值视为点按锁定
+    pub tap_threshold_ms: u32,
+    /// 声控模式下按住快捷键的覆盖行为
+    pub vox_override: VoxOverride,
 }
 
 impl Default for HotkeyConfig {
@@ -48,20 +109,332 @@ impl Default for HotkeyConfig {
         Self {
             accelerator: "Ctrl+Shift+V".to_string(),
             mode: GateMode::Ptt,
+            hang_time_ms: 0,
+            tap_threshold_ms: 250,
+            vox_override: VoxOverride::Mute,
         }
     }
 }
 
+/// 多声道输入取哪个声道作为麦克风信号
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InputChannel {
+    /// 全部声道取平均
+    #[default]
+    Average,
+    Left,
+    Right,
+    /// 指定声道，从 0 开始
+    Channel {
+        index: u16,
+    },
+    /// 自动选择能量最大的声道
+    Auto,
+}
+
+/// 输入设备的选择方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InputRouteMode {
+    /// 使用 `input_device_id` 指定的设备，不可用时按备用设备列表切换
+    #[default]
+    Fixed,
+    /// 跟随系统默认录音设备，默认设备变化时交叉淡化切换
+    FollowDefault,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct AudioRouteConfig {
+    #[serde(default)]
+    pub input_mode: InputRouteMode,
+    /// 固定模式下的首选输入设备；跟随默认设备时在默认设备不可用时使用
     pub input_device_id: String,
     pub bridge_output_device_id: String,
+    /// 回声消除参考设备（输出设备 id），从其环回采集远端信号；为空时不启用回声消除
+    #[serde(default)]
+    pub reference_output_device_id: String,
+    #[serde(default)]
+    pub input_channel: InputChannel,
+    /// 直通模式：保留输入声道布局一路送到输出，忽略 `input_channel`
+    #[serde(default)]
+    pub passthrough: bool,
+    /// 承载信号的输出声道（从 0 开始）；为空时写入全部声道
+    #[serde(default)]
+    pub output_channels: Vec<u16>,
+    /// 首选输入设备不可用时依次尝试的备用输入设备，按优先级排列
+    #[serde(default)]
+    pub input_fallback_ids: Vec<String>,
+    /// 首选输出设备不可用时依次尝试的备用输出设备，按优先级排列
+    #[serde(default)]
+    pub output_fallback_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteChangeReason {
+    /// 正在使用的设备已不可用，切换到下一个可用设备
+    DeviceLost,
+    /// 优先级更高的设备重新出现，切回该设备
+    PreferredRestored,
+    /// 跟随系统默认输入时默认设备发生变化
+    DefaultChanged,
+}
+
+/// 运行中自动切换设备时发出的 `route_changed` 事件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RouteChange {
+    pub direction: DeviceDirection,
+    pub from_device_id: String,
+    pub to_device_id: String,
+    pub to_device_name: String,
+    pub reason: RouteChangeReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvelopeCurve {
+    Linear,
+    #[default]
+    EqualPower,
+    Exponential,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct EnvelopeConfig {
+    pub attack_ms: u32,
+    pub release_ms: u32,
+    pub curve: EnvelopeCurve,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            attack_ms: 8,
+            release_ms: 20,
+            curve: EnvelopeCurve::EqualPower,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VoxConfig {
+    /// 开麦阈值（dBFS）
+    pub open_threshold_db: f32,
+    /// 闭麦阈值（dBFS），低于开麦阈值形成迟滞
+    pub close_threshold_db: f32,
+    /// 电平需持续高于开麦阈值的时长（毫秒）
+    pub attack_ms: u32,
+    /// 电平低于闭麦阈值后继续保持开麦的时长（毫秒）
+    pub hold_ms: u32,
+    /// 每次开麦的最短持续时长（毫秒）
+    pub min_open_ms: u32,
+}
+
+impl Default for VoxConfig {
+    fn default() -> Self {
+        Self {
+            open_threshold_db: -40.0,
+            close_threshold_db: -48.0,
+            attack_ms: 10,
+            hold_ms: 400,
+            min_open_ms: 250,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VadConfig {
+    /// 语音概率高于该值时开麦
+    pub open_probability: f32,
+    /// 语音概率低于该值时开始计算保持时长
+    pub close_probability: f32,
+    /// 概率低于闭麦阈值后继续保持开麦的时长（毫秒）
+    pub hold_ms: u32,
+    /// 每次开麦的最短持续时长（毫秒）
+    pub min_open_ms: u32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            open_probability: 0.6,
+            close_probability: 0.3,
+            hold_ms: 300,
+            min_open_ms: 250,
+        }
+    }
+}
+
+/// 闭麦说话提醒参数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MutedSpeechConfig {
+    pub enabled: bool,
+    /// 触发电平（dBFS RMS），越低越灵敏
+    pub threshold_db: f32,
+    /// 持续说话多久后提醒（毫秒）
+    pub min_speech_ms: u32,
+    /// 两次提醒之间的最短间隔（秒）
+    pub cooldown_secs: u32,
+}
+
+impl Default for MutedSpeechConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_db: -42.0,
+            min_speech_ms: 1500,
+            cooldown_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EngineConfig {
+    /// 抖动缓冲目标时延（毫秒）
+    pub target_latency_ms: u32,
+    /// 开闭麦增益包络
+    pub envelope: EnvelopeConfig,
+    /// 开麦时回溯播放的预录时长（毫秒），0 表示关闭
+    pub preroll_ms: u32,
+    /// 声控门限参数
+    pub vox: VoxConfig,
+    /// 语音活动检测参数
+    pub vad: VadConfig,
+    /// 闭麦说话提醒
+    pub muted_speech: MutedSpeechConfig,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            target_latency_ms: 30,
+            envelope: EnvelopeConfig::default(),
+            preroll_ms: 150,
+            vox: VoxConfig::default(),
+            vad: VadConfig::default(),
+            muted_speech: MutedSpeechConfig::default(),
+        }
+    }
+}
+
+/// 处理器参数，按 `kind` 区分处理器类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProcessorSettings {
+    Gain {
+        /// 增益（dB）
+        gain_db: f32,
+    },
+    NoiseSuppression {
+        /// 降噪强度（0~1），1 对应最大 30dB 衰减
+        strength: f32,
+    },
+    Agc {
+        /// 目标响度（dBFS RMS）
+        target_db: f32,
+        /// 最大提升/衰减量（dB）
+        max_gain_db: f32,
+    },
+    Compressor {
+        /// 压缩门限（dBFS）
+        threshold_db: f32,
+        /// 压缩比，如 4 表示超出门限部分压缩为 1/4
+        ratio: f32,
+        /// 软拐点宽度（dB），0 为硬拐点
+        knee_db: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        /// 补偿增益（dB）
+        makeup_db: f32,
+    },
+    Limiter {
+        /// 输出峰值上限（dBFS）
+        ceiling_db: f32,
+        /// 前瞻时间（毫秒），同时也是引入的时延
+        lookahead_ms: f32,
+        release_ms: f32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProcessorConfig {
+    /// 处理器唯一标识，重排时据此保留处理器内部状态
+    pub id: u32,
+    #[serde(default)]
+    pub bypass: bool,
+    #[serde(flatten)]
+    pub settings: ProcessorSettings,
+}
+
+/// 采集与门控之间的处理链，按列表顺序依次处理
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct DspChainConfig {
+    pub processors: Vec<ProcessorConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EqBandKind {
+    #[default]
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+/// 参数均衡的单个频段
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EqBand {
+    pub kind: EqBandKind,
+    /// 中心频率或搁架转折频率（Hz）
+    pub frequency_hz: f32,
+    pub gain_db: f32,
+    pub q: f32,
+}
+
+impl Default for EqBand {
+    fn default() -> Self {
+        Self {
+            kind: EqBandKind::Peaking,
+            frequency_hz: 1000.0,
+            gain_db: 0.0,
+            q: 1.0,
+        }
+    }
+}
+
+/// 输入设备的基础音色设置，位于处理链之前
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct VoiceShapingConfig {
+    /// 输入增益（dB）
+    pub input_gain_db: f32,
+    /// 高通滤波截止频率（Hz），0 表示关闭
+    pub high_pass_hz: f32,
+    pub eq: Vec<EqBand>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub route: AudioRouteConfig,
     pub hotkey: HotkeyConfig,
+    #[serde(default)]
+    pub engine: EngineConfig,
+    #[serde(default)]
+    pub dsp: DspChainConfig,
+    /// 按输入设备 id 保存的音色设置
+    #[serde(default)]
+    pub input_profiles: BTreeMap<String, VoiceShapingConfig>,
+    /// 配置中引用过的设备指纹，按设备 id 保存，设备 id 失效时据此重新匹配
+    #[serde(default)]
+    pub device_fingerprints: BTreeMap<String, DeviceFingerprint>,
     pub launch_on_startup: bool,
     pub minimize_to_tray: bool,
 }
@@ -71,6 +444,10 @@ impl Default for AppConfig {
         Self {
             route: AudioRouteConfig::default(),
             hotkey: HotkeyConfig::default(),
+            engine: EngineConfig::default(),
+            dsp: DspChainConfig::default(),
+            input_profiles: BTreeMap::new(),
+            device_fingerprints: BTreeMap::new(),
             launch_on_startup: false,
             minimize_to_tray: true,
         }
@@ -83,6 +460,9 @@ pub struct GateState {
     pub mode: GateMode,
     pub last_source: String,
     pub changed_at: DateTime<Utc>,
+    /// 松键挂起期间的计划闭麦时间，供界面显示倒计时
+    #[serde(default)]
+    pub pending_close_at: Option<DateTime<Utc>>,
 }
 
 impl Default for GateState {
@@ -92,6 +472,7 @@ impl Default for GateState {
             mode: GateMode::Ptt,
             last_source: "system".to_string(),
             changed_at: Utc::now(),
+            pending_close_at: None,
         }
     }
 }
@@ -109,6 +490,25 @@ pub struct RuntimeStatus {
     pub engine_state: EngineState,
     pub buffer_level_ms: u32,
     pub xruns: u64,
+    pub overflows: u64,
+    pub underflows: u64,
+    pub target_latency_ms: u32,
+    /// 当前时钟漂移修正量（ppm），正值表示输入时钟偏快
+    pub drift_ppm: i32,
+    pub input_sample_rate: u32,
+    pub output_sample_rate: u32,
+    /// 输出采样率 / 输入采样率，1.0 表示无需重采样
+    pub resample_ratio: f64,
+    /// 处理链引入的时延（毫秒）
+    pub dsp_latency_ms: f32,
+    /// 语音活动检测的实时语音概率（0~1）
+    pub speech_probability: f32,
+    /// 处理链当前的总增益衰减（dB），来自自动增益、压缩与限幅
+    pub gain_reduction_db: f32,
+    /// 实际使用的输入设备，首选设备不可用时为备用设备
+    pub active_input_device_id: String,
+    /// 实际使用的输出设备
+    pub active_output_device_id: String,
     pub last_error: Option<String>,
     pub gate_state: GateState,
 }
@@ -119,12 +519,57 @@ impl Default for RuntimeStatus {
             engine_state: EngineState::Idle,
             buffer_level_ms: 0,
             xruns: 0,
+            overflows: 0,
+            underflows: 0,
+            target_latency_ms: 0,
+            drift_ppm: 0,
+            input_sample_rate: 0,
+            output_sample_rate: 0,
+            resample_ratio: 1.0,
+            dsp_latency_ms: 0.0,
+            speech_probability: 0.0,
+            gain_reduction_db: 0.0,
+            active_input_device_id: String::new(),
+            active_output_device_id: String::new(),
             last_error: None,
             gate_state: GateState::default(),
         }
     }
 }
 
+/// 单个测量点的电平（dBFS）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct LevelReading {
+    pub peak_db: f32,
+    pub rms_db: f32,
+}
+
+impl Default for LevelReading {
+    fn default() -> Self {
+        // 与电平计下限一致，表示静音
+        Self {
+            peak_db: FLOOR_DB,
+            rms_db: FLOOR_DB,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct AudioLevels {
+    /// 门控前：采集并经处理链后的信号
+    pub pre_gate: LevelReading,
+    /// 门控后：实际写入虚拟麦的信号
+    pub post_gate: LevelReading,
+}
+
+/// `speaking_while_muted` 事件负载
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MutedSpeechWarning {
+    /// 触发时门控前的电平
+    pub level: LevelReading,
+    pub gate_state: GateState,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VirtualMicStatus {
     pub backend: String,
@@ -0,0 +1,291 @@
+use crate::{dsp::fft::Fft, types::VadConfig};
+
+/// 分析帧长（毫秒）
+const FRAME_MS: u32 = 20;
+/// 语音频带（Hz），按电话频带取值
+const SPEECH_BAND_HZ: (f32, f32) = (300.0, 3400.0);
+/// 参与频带占比计算的最低频率，排除直流与工频附近
+const TOTAL_BAND_LOW_HZ: f32 = 60.0;
+/// 绝对静音门限（dBFS），低于该电平的帧直接判为非语音
+const SILENCE_DB: f32 = -65.0;
+/// 噪声底每秒上升量（dB），下降则立即跟随
+const NOISE_FLOOR_RISE_DB_PER_SEC: f32 = 3.0;
+/// 帧概率平滑系数
+const PROBABILITY_SMOOTHING: f32 = 0.4;
+
+/// 频谱语音活动检测。
+///
+/// 按 20ms 帧综合短时能量（相对自适应噪声底）、过零率、语音频带能量占比
+/// 与频带内谱平坦度给出语音概率，再以概率迟滞驱动门控。
+/// 所有缓冲在创建时分配，可在实时回调内调用。
+pub struct VoiceActivityDetector {
+    frame: Vec<f32>,
+    filled: usize,
+    window: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    fft: Fft,
+    bin_hz: f32,
+    frame_secs: f32,
+    noise_floor_db: f32,
+    probability: f32,
+    open_probability: f32,
+    close_probability: f32,
+    hold_frames: usize,
+    min_open_frames: usize,
+    open: bool,
+    below: usize,
+    open_for: usize,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(config: &VadConfig, sample_rate: u32) -> Self {
+        let frame_len = (sample_rate * FRAME_MS / 1000).max(16) as usize;
+        let fft_len = frame_len.next_power_of_two();
+        let frames = |ms: u32| ms.div_ceil(FRAME_MS) as usize;
+
+        let window = (0..frame_len)
+            .map(|i| {
+                let x = i as f32 / (frame_len - 1) as f32;
+                0.5 - 0.5 * (2.0 * std::f32::consts::PI * x).cos()
+            })
+            .collect();
+
+        Self {
+            frame: vec![0.0; frame_len],
+            filled: 0,
+            window,
+            re: vec![0.0; fft_len],
+            im: vec![0.0; fft_len],
+            fft: Fft::new(fft_len),
+            bin_hz: sample_rate as f32 / fft_len as f32,
+            frame_secs: frame_len as f32 / sample_rate as f32,
+            noise_floor_db: SILENCE_DB,
+            probability: 0.0,
+            open_probability: config.open_probability,
+            close_probability: config.close_probability.min(config.open_probability),
+            hold_frames: frames(config.hold_ms),
+            min_open_frames: frames(config.min_open_ms),
+            open: false,
+            below: 0,
+            open_for: 0,
+        }
+    }
+
+    /// 最近一帧平滑后的语音概率（0~1）
+    pub fn probability(&self) -> f32 {
+        self.probability
+    }
+
+    /// 喂入单声道样本，按帧更新概率；门控状态翻转时返回新状态
+    pub fn process(&mut self, block: &[f32]) -> Option<bool> {
+        let mut edge = None;
+        for &sample in block {
+            self.frame[self.filled] = sample;
+            self.filled += 1;
+            if self.filled < self.frame.len() {
+                continue;
+            }
+            self.filled = 0;
+
+            let raw = self.analyze_frame();
+            self.probability += (raw - self.probability) * PROBABILITY_SMOOTHING;
+            if let Some(open) = self.update_gate() {
+                edge = Some(open);
+            }
+        }
+        edge
+    }
+
+    fn update_gate(&mut self) -> Option<bool> {
+        if !self.open {
+            if self.probability < self.open_probability {
+                return None;
+            }
+            self.open = true;
+            self.below = 0;
+            self.open_for = 0;
+            return Some(true);
+        }
+
+        self.open_for += 1;
+        if self.probability >= self.close_probability {
+            self.below = 0;
+            return None;
+        }
+        self.below += 1;
+        if self.below < self.hold_frames || self.open_for < self.min_open_frames {
+            return None;
+        }
+        self.open = false;
+        Some(false)
+    }
+
+    /// 计算当前帧的原始语音概率
+    fn analyze_frame(&mut self) -> f32 {
+        let len = self.frame.len();
+        let power = self.frame.iter().map(|s| s * s).sum::<f32>() / len as f32;
+        let energy_db = 10.0 * (power + 1e-12).log10();
+
+        if energy_db < self.noise_floor_db {
+            self.noise_floor_db = energy_db.max(SILENCE_DB - 20.0);
+        } else {
+            self.noise_floor_db += NOISE_FLOOR_RISE_DB_PER_SEC * self.frame_secs;
+        }
+        if energy_db < SILENCE_DB {
+            return 0.0;
+        }
+        // 高出噪声底 6dB 起算，18dB 以上视为满分
+        let energy_score = ((energy_db - self.noise_floor_db - 6.0) / 12.0).clamp(0.0, 1.0);
+
+        // 过零率折算成主频估计：浊音集中在低频，白噪声与按键声远高于此
+        let crossings = self
+            .frame
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count();
+        let zcr_hz = crossings as f32 / self.frame_secs / 2.0;
+        let zcr_score = ((5000.0 - zcr_hz) / 2500.0).clamp(0.0, 1.0);
+
+        for (i, (re, im)) in self.re.iter_mut().zip(self.im.iter_mut()).enumerate() {
+            *re = if i < len {
+                self.frame[i] * self.window[i]
+            } else {
+                0.0
+            };
+            *im = 0.0;
+        }
+        self.fft.forward(&mut self.re, &mut self.im);
+
+        let half = self.re.len() / 2;
+        let low = (TOTAL_BAND_LOW_HZ / self.bin_hz).ceil() as usize;
+        let band_lo = (SPEECH_BAND_HZ.0 / self.bin_hz).ceil() as usize;
+        let band_hi = ((SPEECH_BAND_HZ.1 / self.bin_hz) as usize).min(half);
+
+        let mut total = 0.0_f32;
+        let mut band = 0.0_f32;
+        let mut log_sum = 0.0_f32;
+        for k in low..=half {
+            let p = self.re[k] * self.re[k] + self.im[k] * self.im[k] + 1e-12;
+            total += p;
+            if (band_lo..=band_hi).contains(&k) {
+                band += p;
+                log_sum += p.ln();
+            }
+        }
+        let band_bins = (band_hi + 1 - band_lo) as f32;
+        let band_ratio = band / total.max(1e-12);
+        // 谐波语音的频谱起伏大、平坦度低；噪声接近平坦
+        let flatness = (log_sum / band_bins).exp() / (band / band_bins).max(1e-12);
+
+        let band_score = ((band_ratio - 0.35) / 0.35).clamp(0.0, 1.0);
+        let flatness_score = ((0.5 - flatness) / 0.35).clamp(0.0, 1.0);
+
+        energy_score * band_score * (0.5 * flatness_score + 0.5 * zcr_score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// 类语音信号：160Hz 基频谐波叠加共振峰包络，并以 4Hz 音节节奏调幅
+    fn speech_like(seconds: f32) -> Vec<f32> {
+        let formant = |f: f32| {
+            (-((f - 600.0) / 500.0).powi(2)).exp()
+                + 0.5 * (-((f - 1800.0) / 600.0).powi(2)).exp()
+                + 0.1
+        };
+        let len = (seconds * RATE as f32) as usize;
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / RATE as f32;
+                let voiced: f32 = (1..=24)
+                    .map(|k| {
+                        let f = 160.0 * k as f32;
+                        formant(f) * (2.0 * std::f32::consts::PI * f * t).sin()
+                    })
+                    .sum();
+                let syllable = 0.6 + 0.4 * (2.0 * std::f32::consts::PI * 4.0 * t).sin();
+                voiced * syllable * 0.05
+            })
+            .collect()
+    }
+
+    fn white_noise(seconds: f32, amplitude: f32, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..(seconds * RATE as f32) as usize)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    /// 风扇噪声：白噪声经 100Hz 一阶低通
+    fn fan_noise(seconds: f32) -> Vec<f32> {
+        let alpha = 1.0 - (-2.0 * std::f32::consts::PI * 100.0 / RATE as f32).exp();
+        let mut y = 0.0;
+        white_noise(seconds, 1.0, 7)
+            .into_iter()
+            .map(|x| {
+                y += (x - y) * alpha;
+                y * 0.5
+            })
+            .collect()
+    }
+
+    /// 键盘敲击：每 150ms 一次 3ms 的宽带噪声脉冲
+    fn key_clicks(seconds: f32) -> Vec<f32> {
+        let burst = white_noise(seconds, 0.6, 11);
+        let period = (RATE as f32 * 0.15) as usize;
+        let click = (RATE as f32 * 0.003) as usize;
+        burst
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| if i % period < click { s } else { 0.0 })
+            .collect()
+    }
+
+    /// 以 10ms 块喂入信号，返回跳过预热段后的平均语音概率与是否曾开麦
+    fn run(signal: &[f32]) -> (f32, bool) {
+        let mut vad = VoiceActivityDetector::new(&VadConfig::default(), RATE);
+        let warmup = RATE as usize / 2;
+        let mut sum = 0.0;
+        let mut count = 0;
+        let mut opened = false;
+        for (n, block) in signal.chunks(480).enumerate() {
+            if vad.process(block) == Some(true) && n * 480 >= warmup {
+                opened = true;
+            }
+            if n * 480 >= warmup {
+                sum += vad.probability();
+                count += 1;
+            }
+        }
+        (sum / count as f32, opened)
+    }
+
+    #[test]
+    fn speech_like_signal_should_score_high() {
+        let (mean, _) = run(&speech_like(3.0));
+        assert!(mean > 0.6, "mean = {mean}");
+    }
+
+    #[test]
+    fn noise_and_clicks_should_score_low_and_keep_gate_closed() {
+        for (name, signal) in [
+            ("white", white_noise(3.0, 0.1, 3)),
+            ("fan", fan_noise(3.0)),
+            ("clicks", key_clicks(3.0)),
+        ] {
+            let (mean, opened) = run(&signal);
+            assert!(mean < 0.2, "{name}: mean = {mean}");
+            assert!(!opened, "{name}: gate opened");
+        }
+    }
+}
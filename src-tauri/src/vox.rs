@@ -0,0 +1,146 @@
+use crate::types::VoxConfig;
+
+/// 声控门限检测。
+///
+/// 按输入块 RMS 电平带迟滞判定：电平持续高于开麦阈值达到起音窗口才开麦；
+/// 开麦后电平低于闭麦阈值持续达到保持时长，且已满最短开麦时长才闭麦。
+pub struct VoxDetector {
+    open_level: f32,
+    close_level: f32,
+    attack: usize,
+    hold: usize,
+    min_open: usize,
+    open: bool,
+    above: usize,
+    below: usize,
+    open_for: usize,
+}
+
+impl VoxDetector {
+    pub fn new(config: &VoxConfig, sample_rate: u32) -> Self {
+        let samples = |ms: u32| (ms as u64 * sample_rate as u64 / 1000) as usize;
+        let close_db = config.close_threshold_db.min(config.open_threshold_db);
+
+        Self {
+            open_level: db_to_linear(config.open_threshold_db),
+            close_level: db_to_linear(close_db),
+            attack: samples(config.attack_ms),
+            hold: samples(config.hold_ms),
+            min_open: samples(config.min_open_ms),
+            open: false,
+            above: 0,
+            below: 0,
+            open_for: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// 处理一个单声道输入块，开闭状态翻转时返回新状态
+    pub fn process(&mut self, block: &[f32]) -> Option<bool> {
+        if block.is_empty() {
+            return None;
+        }
+        let level = rms(block);
+
+        if !self.open {
+            if level < self.open_level {
+                self.above = 0;
+                return None;
+            }
+            self.above += block.len();
+            if self.above < self.attack {
+                return None;
+            }
+            self.open = true;
+            self.above = 0;
+            self.below = 0;
+            self.open_for = 0;
+            return Some(true);
+        }
+
+        self.open_for += block.len();
+        if level >= self.close_level {
+            self.below = 0;
+            return None;
+        }
+        self.below += block.len();
+        if self.below < self.hold || self.open_for < self.min_open {
+            return None;
+        }
+        self.open = false;
+        self.below = 0;
+        Some(false)
+    }
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+fn rms(block: &[f32]) -> f32 {
+    let energy: f32 = block.iter().map(|s| s * s).sum();
+    (energy / block.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按 1ms 一块喂入指定电平的方波，返回每块的翻转结果
+    fn feed(vox: &mut VoxDetector, amplitude: f32, ms: usize) -> Vec<Option<bool>> {
+        let block: Vec<f32> = (0..48)
+            .map(|i| if i % 2 == 0 { amplitude } else { -amplitude })
+            .collect();
+        (0..ms).map(|_| vox.process(&block)).collect()
+    }
+
+    #[test]
+    fn vox_should_open_after_attack_and_close_after_hold() {
+        let config = VoxConfig {
+            open_threshold_db: -30.0,
+            close_threshold_db: -40.0,
+            attack_ms: 10,
+            hold_ms: 100,
+            min_open_ms: 0,
+        };
+        let mut vox = VoxDetector::new(&config, 48_000);
+
+        // -20 dBFS 持续 10ms 后开麦
+        let edges = feed(&mut vox, 0.1, 12);
+        assert_eq!(edges.iter().position(|e| *e == Some(true)), Some(9));
+
+        // -35 dBFS 位于迟滞区间内，保持开麦
+        assert!(feed(&mut vox, 0.0178, 500).iter().all(Option::is_none));
+        assert!(vox.is_open());
+
+        // 静音持续 100ms 后闭麦
+        let edges = feed(&mut vox, 0.0, 120);
+        assert_eq!(edges.iter().position(|e| *e == Some(false)), Some(99));
+    }
+
+    #[test]
+    fn short_burst_should_not_open_and_min_open_should_hold() {
+        let config = VoxConfig {
+            open_threshold_db: -30.0,
+            close_threshold_db: -40.0,
+            attack_ms: 10,
+            hold_ms: 20,
+            min_open_ms: 300,
+        };
+        let mut vox = VoxDetector::new(&config, 48_000);
+
+        // 5ms 的按键咔哒声不足起音窗口
+        feed(&mut vox, 0.5, 5);
+        feed(&mut vox, 0.0, 50);
+        assert!(!vox.is_open());
+
+        // 开麦后即使很快静音，也要满 300ms 才闭麦
+        feed(&mut vox, 0.1, 10);
+        assert!(vox.is_open());
+        let edges = feed(&mut vox, 0.0, 400);
+        assert_eq!(edges.iter().position(|e| *e == Some(false)), Some(299));
+    }
+}
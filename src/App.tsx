@@ -1,31 +1,236 @@
 import { useCallback, useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '@/components/ui/select';
 import { Switch } from '@/components/ui/switch';
-import type { AppConfig, DeviceList, GateMode, RuntimeStatus, VirtualMicStatus } from '@/lib/types';
+import type {
+  AppConfig,
+  AudioLevels,
+  DeviceInfo,
+  DeviceList,
+  DevicesChanged,
+  EqBand,
+  EqBandKind,
+  GateMode,
+  InputChannel,
+  ProcessorConfig,
+  ProcessorSettings,
+  LevelReading,
+  MutedSpeechWarning,
+  RouteChange,
+  RuntimeStatus,
+  VirtualMicStatus,
+  VoiceShapingConfig,
+  VoxOverride,
+} from '@/lib/types';
 
 const DEFAULT_CONFIG: AppConfig = {
-  route: { input_device_id: '', bridge_output_device_id: '' },
-  hotkey: { accelerator: 'Ctrl+Shift+V', mode: 'ptt' },
+  route: {
+    input_mode: 'fixed',
+    input_device_id: '',
+    bridge_output_device_id: '',
+    reference_output_device_id: '',
+    input_channel: { kind: 'average' },
+    passthrough: false,
+    output_channels: [],
+    input_fallback_ids: [],
+    output_fallback_ids: [],
+  },
+  hotkey: {
+    accelerator: 'Ctrl+Shift+V',
+    mode: 'ptt',
+    hang_time_ms: 0,
+    tap_threshold_ms: 250,
+    vox_override: 'mute',
+  },
+  engine: {
+    target_latency_ms: 30,
+    envelope: { attack_ms: 8, release_ms: 20, curve: 'equal_power' },
+    preroll_ms: 150,
+    vox: { open_threshold_db: -40, close_threshold_db: -48, attack_ms: 10, hold_ms: 400, min_open_ms: 250 },
+    vad: { open_probability: 0.6, close_probability: 0.3, hold_ms: 300, min_open_ms: 250 },
+    muted_speech: { enabled: true, threshold_db: -42, min_speech_ms: 1500, cooldown_secs: 30 },
+  },
+  dsp: { processors: [] },
+  input_profiles: {},
+  device_fingerprints: {},
   launch_on_startup: false,
   minimize_to_tray: true,
 };
 
+const PROCESSOR_DEFAULTS: Record<ProcessorSettings['kind'], ProcessorSettings> = {
+  gain: { kind: 'gain', gain_db: 0 },
+  noise_suppression: { kind: 'noise_suppression', strength: 0.7 },
+  agc: { kind: 'agc', target_db: -18, max_gain_db: 12 },
+  compressor: {
+    kind: 'compressor',
+    threshold_db: -24,
+    ratio: 3,
+    knee_db: 6,
+    attack_ms: 5,
+    release_ms: 120,
+    makeup_db: 0,
+  },
+  limiter: { kind: 'limiter', ceiling_db: -1, lookahead_ms: 5, release_ms: 60 },
+};
+
+// 选择框不支持空值，回声消除关闭时使用占位值
+const AEC_OFF = '__off__';
+const FOLLOW_DEFAULT_INPUT = '__default__';
+
+/** 输入声道选择在下拉框中的取值，指定声道编码为 `channel:<index>` */
+function inputChannelValue(channel: InputChannel) {
+  return channel.kind === 'channel' ? `channel:${channel.index}` : channel.kind;
+}
+
+function parseInputChannel(value: string): InputChannel {
+  if (value.startsWith('channel:')) return { kind: 'channel', index: Number(value.slice(8)) };
+  return { kind: value as 'average' | 'left' | 'right' | 'auto' };
+}
+
+const DEFAULT_VOICE_PROFILE: VoiceShapingConfig = { input_gain_db: 0, high_pass_hz: 0, eq: [] };
+const MAX_EQ_BANDS = 8;
+
+function eqBandLabel(kind: EqBandKind): string {
+  if (kind === 'low_shelf') return '低搁架';
+  if (kind === 'high_shelf') return '高搁架';
+  return '峰值';
+}
+
+function processorLabel(kind: ProcessorSettings['kind']): string {
+  if (kind === 'gain') return '增益';
+  if (kind === 'noise_suppression') return '降噪';
+  if (kind === 'agc') return '自动增益';
+  if (kind === 'compressor') return '压缩器';
+  if (kind === 'limiter') return '限幅器';
+  return kind;
+}
+
+function moveItem<T>(items: T[], index: number, offset: number): T[] {
+  const target = index + offset;
+  if (target < 0 || target >= items.length) return items;
+  const next = [...items];
+  [next[index], next[target]] = [next[target], next[index]];
+  return next;
+}
+
 const MODIFIER_KEYS = new Set(['Control', 'Shift', 'Alt', 'Meta']);
 
 function modeLabel(mode: GateMode): string {
   if (mode === 'ptt') return '按住说话';
   if (mode === 'toggle') return '切换开关';
+  if (mode === 'push_to_mute') return '按键静音';
+  if (mode === 'vox') return '声控';
+  if (mode === 'vad') return '语音检测';
   return '混合模式';
 }
 
+function pendingCloseLabel(gate?: RuntimeStatus['gate_state']): string {
+  if (!gate?.pending_close_at) return '无';
+  const remaining = Math.max(0, new Date(gate.pending_close_at).getTime() - Date.now());
+  return `${remaining} ms 后闭麦`;
+}
+
 function engineLabel(state?: RuntimeStatus['engine_state']): string {
   if (state === 'running') return '已就绪';
   if (state === 'error') return '错误';
   return '未就绪';
 }
 
+// 电平条显示范围（dBFS）
+const METER_FLOOR_DB = -60;
+
+function meterPercent(db: number): number {
+  return Math.min(100, Math.max(0, ((db - METER_FLOOR_DB) / -METER_FLOOR_DB) * 100));
+}
+
+function LevelBar({ label, reading }: { label: string; reading?: LevelReading }) {
+  const rms = reading?.rms_db ?? -100;
+  const peak = reading?.peak_db ?? -100;
+  return (
+    <div className="space-y-1">
+      <div className="flex justify-between text-xs opacity-70">
+        <span>{label}</span>
+        <span>
+          RMS {rms <= -100 ? '-∞' : rms.toFixed(0)} / 峰值 {peak <= -100 ? '-∞' : peak.toFixed(0)} dBFS
+        </span>
+      </div>
+      <div className="relative h-2 overflow-hidden rounded bg-muted">
+        <div className="h-full bg-primary" style={{ width: `${meterPercent(rms)}%` }} />
+        <div className="absolute top-0 h-full w-0.5 bg-foreground" style={{ left: `${meterPercent(peak)}%` }} />
+      </div>
+    </div>
+  );
+}
+
+function FallbackList({
+  label,
+  primaryId,
+  ids,
+  devices,
+  onChange,
+}: {
+  label: string;
+  primaryId: string;
+  ids: string[];
+  devices: DeviceInfo[];
+  onChange: (ids: string[]) => void;
+}) {
+  const candidates = devices.filter((device) => device.id !== primaryId && !ids.includes(device.id));
+  return (
+    <div>
+      <p className="mb-1 text-sm">{label}</p>
+      <div className="space-y-1">
+        {ids.map((id, index) => (
+          <div key={id} className="flex items-center gap-2 rounded-lg border border-border px-3 py-1">
+            <span className="flex-1 truncate text-sm">
+              {index + 1}. {devices.find((device) => device.id === id)?.name ?? '未连接的设备'}
+            </span>
+            <Button variant="ghost" size="sm" disabled={index === 0} onClick={() => onChange(moveItem(ids, index, -1))}>
+              上移
+            </Button>
+            <Button
+              variant="ghost"
+              size="sm"
+              disabled={index === ids.length - 1}
+              onClick={() => onChange(moveItem(ids, index, 1))}
+            >
+              下移
+            </Button>
+            <Button variant="ghost" size="sm" onClick={() => onChange(ids.filter((item) => item !== id))}>
+              移除
+            </Button>
+          </div>
+        ))}
+        {candidates.length > 0 && (
+          <Select value="" onValueChange={(value) => onChange([...ids, value])}>
+            <SelectTrigger>
+              <SelectValue placeholder="添加备用设备" />
+            </SelectTrigger>
+            <SelectContent>
+              {candidates.map((device) => (
+                <SelectItem key={device.id} value={device.id}>
+                  {device.name}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+        )}
+      </div>
+    </div>
+  );
+}
+
+function routeChangeMessage(change: RouteChange): string {
+  const direction = change.direction === 'input' ? '输入' : '输出';
+  const name = change.to_device_name || change.to_device_id;
+  if (change.reason === 'default_changed') return `系统默认输入已变化，已切换到：${name}`;
+  if (change.reason === 'preferred_restored') return `优先级更高的${direction}设备已恢复，已切回：${name}`;
+  return `${direction}设备已断开，已切换到备用设备：${name}`;
+}
+
 function configSignature(config: AppConfig): string {
   return JSON.stringify(config);
 }
@@ -105,6 +310,7 @@ export default function App() {
   const [recordingHotkey, setRecordingHotkey] = useState(false);
   const [bootstrapped, setBootstrapped] = useState(false);
   const [autoSaving, setAutoSaving] = useState(false);
+  const [levels, setLevels] = useState<AudioLevels | null>(null);
 
   const lastSavedSignatureRef = useRef('');
   const lastSavedConfigRef = useRef<AppConfig>(DEFAULT_CONFIG);
@@ -143,6 +349,98 @@ export default function App() {
     return () => clearInterval(timer);
   }, [refresh]);
 
+  useEffect(() => {
+    const unlisten = listen<AudioLevels>('audio_levels', (event) => setLevels(event.payload));
+    invoke<AudioLevels>('get_audio_levels')
+      .then(setLevels)
+      .catch(() => undefined);
+    return () => {
+      unlisten.then((dispose) => dispose()).catch(() => undefined);
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<DevicesChanged>('devices_changed', (event) => setDevices(event.payload.devices));
+    return () => {
+      unlisten.then((dispose) => dispose()).catch(() => undefined);
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<RouteChange>('route_changed', (event) => setMessage(routeChangeMessage(event.payload)));
+    return () => {
+      unlisten.then((dispose) => dispose()).catch(() => undefined);
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<MutedSpeechWarning>('speaking_while_muted', () =>
+      setMessage('你似乎在说话，但麦克风当前处于闭麦状态'),
+    );
+    return () => {
+      unlisten.then((dispose) => dispose()).catch(() => undefined);
+    };
+  }, []);
+
+  const engineRunning = status?.engine_state === 'running';
+  const followDefaultInput = config.route.input_mode === 'follow_default';
+  // 跟随系统默认输入时以实际使用的设备为准，引擎未运行时取当前默认设备
+  const inputDevice = followDefaultInput
+    ? (devices.inputs.find((device) => device.id === status?.active_input_device_id) ??
+      devices.inputs.find((device) => device.is_default))
+    : devices.inputs.find((device) => device.id === config.route.input_device_id);
+  const profileDeviceId = inputDevice?.id ?? config.route.input_device_id;
+  const bridgeOutput = devices.outputs.find((device) => device.id === config.route.bridge_output_device_id);
+  const activeInput =
+    engineRunning && !followDefaultInput && status?.active_input_device_id !== config.route.input_device_id
+      ? devices.inputs.find((device) => device.id === status.active_input_device_id)
+      : undefined;
+  const activeOutput =
+    engineRunning && status?.active_output_device_id !== config.route.bridge_output_device_id
+      ? devices.outputs.find((device) => device.id === status.active_output_device_id)
+      : undefined;
+
+  const updateProcessors = (update: (processors: ProcessorConfig[]) => ProcessorConfig[]) => {
+    setConfig((previous) => ({
+      ...previous,
+      dsp: { processors: update(previous.dsp.processors) },
+    }));
+  };
+
+  const inputProfile = config.input_profiles[profileDeviceId] ?? DEFAULT_VOICE_PROFILE;
+
+  const updateInputProfile = (update: (profile: VoiceShapingConfig) => VoiceShapingConfig) => {
+    setConfig((previous) => {
+      const deviceId = profileDeviceId;
+      if (!deviceId) return previous;
+      return {
+        ...previous,
+        input_profiles: {
+          ...previous.input_profiles,
+          [deviceId]: update(previous.input_profiles[deviceId] ?? DEFAULT_VOICE_PROFILE),
+        },
+      };
+    });
+  };
+
+  const updateEqBand = (index: number, patch: Partial<EqBand>) => {
+    updateInputProfile((profile) => ({
+      ...profile,
+      eq: profile.eq.map((band, i) => (i === index ? { ...band, ...patch } : band)),
+    }));
+  };
+
+  const addProcessor = (kind: ProcessorSettings['kind']) => {
+    updateProcessors((processors) => [
+      ...processors,
+      {
+        ...PROCESSOR_DEFAULTS[kind],
+        id: Math.max(0, ...processors.map((processor) => processor.id)) + 1,
+        bypass: false,
+      },
+    ]);
+  };
+
   const beginHotkeyRecording = () => {
     recordingStartAtRef.current = Date.now();
     setRecordingHotkey(true);
@@ -223,18 +521,16 @@ export default function App() {
       try {
         await invoke('save_audio_route', { config: config.route });
         await invoke('set_hotkey', { config: config.hotkey });
+        await invoke('set_engine_config', { config: config.engine });
+        await invoke('set_dsp_chain', { config: config.dsp });
+        for (const [deviceId, profile] of Object.entries(config.input_profiles)) {
+          if (JSON.stringify(previous.input_profiles[deviceId]) !== JSON.stringify(profile)) {
+            await invoke('set_input_profile', { deviceId, profile });
+          }
+        }
         await invoke('set_launch_on_startup', { enabled: config.launch_on_startup });
         await invoke('set_minimize_to_tray', { enabled: config.minimize_to_tray });
 
-        const routeChanged =
-          previous.route.input_device_id !== config.route.input_device_id ||
-          previous.route.bridge_output_device_id !== config.route.bridge_output_device_id;
-
-        if (routeChanged) {
-          await invoke('stop_engine');
-          await invoke('start_engine');
-        }
-
         const [runtime, vmStatus] = await Promise.all([
           invoke<RuntimeStatus>('get_runtime_status'),
           invoke<VirtualMicStatus>('get_virtual_mic_status'),
@@ -267,11 +563,19 @@ export default function App() {
             <div>
               <p className="mb-1 text-sm">物理麦克风输入</p>
               <Select
-                value={config.route.input_device_id}
+                value={followDefaultInput ? FOLLOW_DEFAULT_INPUT : config.route.input_device_id}
                 onValueChange={(value) =>
                   setConfig((previous) => ({
                     ...previous,
-                    route: { ...previous.route, input_device_id: value },
+                    route: {
+                      ...previous.route,
+                      // 跟随默认设备时保留原首选设备，默认设备不可用时使用
+                      input_mode: value === FOLLOW_DEFAULT_INPUT ? 'follow_default' : 'fixed',
+                      input_device_id: value === FOLLOW_DEFAULT_INPUT ? previous.route.input_device_id : value,
+                      // 指定声道编号只对原设备有意义
+                      input_channel:
+                        previous.route.input_channel.kind === 'channel' ? { kind: 'average' } : previous.route.input_channel,
+                    },
                   }))
                 }
               >
@@ -279,6 +583,7 @@ export default function App() {
                   <SelectValue placeholder="请选择输入设备" />
                 </SelectTrigger>
                 <SelectContent>
+                  <SelectItem value={FOLLOW_DEFAULT_INPUT}>跟随系统默认输入</SelectItem>
                   {devices.inputs.map((device) => (
                     <SelectItem key={device.id} value={device.id}>
                       {device.name}
@@ -287,9 +592,175 @@ export default function App() {
                   ))}
                 </SelectContent>
               </Select>
+              {activeInput && <p className="mt-1 text-xs opacity-70">首选设备不可用，当前使用：{activeInput.name}</p>}
+              {followDefaultInput && (
+                <p className="mt-1 text-xs opacity-70">
+                  当前默认输入：{inputDevice?.name ?? '未检测到'}。系统默认录音设备变化时自动交叉淡化切换。
+                </p>
+              )}
+            </div>
+            <FallbackList
+              label="备用输入设备"
+              primaryId={config.route.input_device_id}
+              ids={config.route.input_fallback_ids}
+              devices={devices.inputs}
+              onChange={(ids) =>
+                setConfig((previous) => ({ ...previous, route: { ...previous.route, input_fallback_ids: ids } }))
+              }
+            />
+            <FallbackList
+              label="备用输出设备"
+              primaryId={config.route.bridge_output_device_id}
+              ids={config.route.output_fallback_ids}
+              devices={devices.outputs}
+              onChange={(ids) =>
+                setConfig((previous) => ({ ...previous, route: { ...previous.route, output_fallback_ids: ids } }))
+              }
+            />
+            {activeOutput && <p className="text-xs opacity-70">首选输出设备不可用，当前使用：{activeOutput.name}</p>}
+            <p className="text-xs opacity-70">
+              使用中的设备断开时按顺序切换到下一个可用设备，优先级更高的设备重新接入后自动切回。
+            </p>
+            <div className="flex items-center justify-between">
+              <span className="text-sm">多声道直通</span>
+              <Switch
+                checked={config.route.passthrough}
+                onCheckedChange={(checked) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    route: { ...previous.route, passthrough: checked },
+                  }))
+                }
+              />
+            </div>
+            {config.route.passthrough && (
+              <p className="text-xs opacity-70">
+                保留输入的声道布局（如立体声麦克风、调音台）一路送到虚拟麦克风，声道数不一致时按固定矩阵上混或下混。
+              </p>
+            )}
+            <div>
+              <p className="mb-1 text-sm">输入声道</p>
+              <Select
+                disabled={config.route.passthrough}
+                value={inputChannelValue(config.route.input_channel)}
+                onValueChange={(value) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    route: { ...previous.route, input_channel: parseInputChannel(value) },
+                  }))
+                }
+              >
+                <SelectTrigger>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="average">全部声道平均</SelectItem>
+                  <SelectItem value="left">左声道</SelectItem>
+                  <SelectItem value="right">右声道</SelectItem>
+                  <SelectItem value="auto">自动（音量最大的声道）</SelectItem>
+                  {Array.from({ length: inputDevice?.channels ?? 0 }, (_, index) => (
+                    <SelectItem key={index} value={`channel:${index}`}>
+                      输入 {index + 1}
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+              <p className="mt-1 text-xs opacity-70">声卡只有某一路接了麦克风时，选择对应输入可避免电平减半和混入空声道噪声。</p>
+            </div>
+            {(bridgeOutput?.channels ?? 0) > 1 && (
+              <div>
+                <p className="mb-1 text-sm">输出声道</p>
+                <div className="flex flex-wrap gap-2">
+                  {Array.from({ length: bridgeOutput?.channels ?? 0 }, (_, index) => {
+                    const selected =
+                      config.route.output_channels.length === 0 || config.route.output_channels.includes(index);
+                    return (
+                      <Button
+                        key={index}
+                        size="sm"
+                        variant={selected ? 'default' : 'outline'}
+                        onClick={() =>
+                          setConfig((previous) => {
+                            const all = Array.from({ length: bridgeOutput?.channels ?? 0 }, (_, i) => i);
+                            const current =
+                              previous.route.output_channels.length === 0 ? all : previous.route.output_channels;
+                            const next = current.includes(index)
+                              ? current.filter((channel) => channel !== index)
+                              : [...current, index].sort((a, b) => a - b);
+                            // 至少保留一个声道；全选时存为空数组，表示写入全部声道
+                            if (next.length === 0) return previous;
+                            return {
+                              ...previous,
+                              route: {
+                                ...previous.route,
+                                output_channels: next.length === all.length ? [] : next,
+                              },
+                            };
+                          })
+                        }
+                      >
+                        {index + 1}
+                      </Button>
+                    );
+                  })}
+                </div>
+                <p className="mt-1 text-xs opacity-70">未选中的输出声道保持静音。</p>
+              </div>
+            )}
+            <div>
+              <p className="mb-1 text-sm">回声消除参考</p>
+              <Select
+                value={config.route.reference_output_device_id || AEC_OFF}
+                onValueChange={(value) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    route: { ...previous.route, reference_output_device_id: value === AEC_OFF ? '' : value },
+                  }))
+                }
+              >
+                <SelectTrigger>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value={AEC_OFF}>关闭</SelectItem>
+                  {devices.outputs
+                    .filter((device) => device.id !== config.route.bridge_output_device_id)
+                    .map((device) => (
+                      <SelectItem key={device.id} value={device.id}>
+                        {device.name}
+                        {device.is_default ? '（系统默认）' : ''}
+                      </SelectItem>
+                    ))}
+                </SelectContent>
+              </Select>
+              <p className="mt-1 text-xs opacity-70">选择外放使用的扬声器，消除游戏与语音声音回灌到麦克风。</p>
+            </div>
+            <div>
+              <p className="mb-1 text-sm">目标缓冲时延</p>
+              <Select
+                value={String(config.engine.target_latency_ms)}
+                onValueChange={(value) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    engine: { ...previous.engine, target_latency_ms: Number(value) },
+                  }))
+                }
+              >
+                <SelectTrigger>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="20">20 ms（低时延）</SelectItem>
+                  <SelectItem value="30">30 ms（推荐）</SelectItem>
+                  <SelectItem value="45">45 ms</SelectItem>
+                  <SelectItem value="50">50 ms（抗抖动）</SelectItem>
+                </SelectContent>
+              </Select>
             </div>
             <div className="space-y-1">
               <p className="text-sm">语音链路状态：{engineLabel(status?.engine_state)}</p>
+              <LevelBar label="麦克风（门控前）" reading={engineRunning ? levels?.pre_gate : undefined} />
+              <LevelBar label="虚拟麦（门控后）" reading={engineRunning ? levels?.post_gate : undefined} />
               <p className="text-xs opacity-70">切换物理麦克风后会自动应用更改，无需手动重置。</p>
             </div>
           </CardContent>
@@ -330,10 +801,242 @@ export default function App() {
                   <SelectItem value="ptt">按住说话</SelectItem>
                   <SelectItem value="toggle">切换开关</SelectItem>
                   <SelectItem value="hybrid">混合模式</SelectItem>
+                  <SelectItem value="push_to_mute">按键静音</SelectItem>
+                  <SelectItem value="vox">声控</SelectItem>
+                  <SelectItem value="vad">语音检测</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+
+            {config.hotkey.mode === 'hybrid' && (
+              <div>
+                <p className="mb-1 text-sm">点按判定</p>
+                <Select
+                  value={String(config.hotkey.tap_threshold_ms)}
+                  onValueChange={(value) =>
+                    setConfig((previous) => ({
+                      ...previous,
+                      hotkey: { ...previous.hotkey, tap_threshold_ms: Number(value) },
+                    }))
+                  }
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="150">150 ms</SelectItem>
+                    <SelectItem value="250">250 ms（推荐）</SelectItem>
+                    <SelectItem value="400">400 ms</SelectItem>
+                    <SelectItem value="600">600 ms</SelectItem>
+                  </SelectContent>
+                </Select>
+                <p className="mt-1 text-xs opacity-70">短于该时长的点按锁定开麦，长按则松开即闭麦。</p>
+              </div>
+            )}
+
+            {config.hotkey.mode === 'vox' && (
+              <>
+                <div>
+                  <p className="mb-1 text-sm">声控阈值</p>
+                  <Select
+                    value={String(config.engine.vox.open_threshold_db)}
+                    onValueChange={(value) =>
+                      setConfig((previous) => ({
+                        ...previous,
+                        engine: {
+                          ...previous.engine,
+                          vox: {
+                            ...previous.engine.vox,
+                            open_threshold_db: Number(value),
+                            close_threshold_db: Number(value) - 8,
+                          },
+                        },
+                      }))
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="-50">-50 dBFS（灵敏）</SelectItem>
+                      <SelectItem value="-45">-45 dBFS</SelectItem>
+                      <SelectItem value="-40">-40 dBFS（推荐）</SelectItem>
+                      <SelectItem value="-35">-35 dBFS</SelectItem>
+                      <SelectItem value="-30">-30 dBFS（迟钝）</SelectItem>
+                    </SelectContent>
+                  </Select>
+                  <p className="mt-1 text-xs opacity-70">电平高于阈值开麦，低于阈值 8 dB 并持续保持时长后闭麦。</p>
+                </div>
+
+                <div>
+                  <p className="mb-1 text-sm">声控保持</p>
+                  <Select
+                    value={String(config.engine.vox.hold_ms)}
+                    onValueChange={(value) =>
+                      setConfig((previous) => ({
+                        ...previous,
+                        engine: { ...previous.engine, vox: { ...previous.engine.vox, hold_ms: Number(value) } },
+                      }))
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="200">200 ms</SelectItem>
+                      <SelectItem value="400">400 ms（推荐）</SelectItem>
+                      <SelectItem value="800">800 ms</SelectItem>
+                      <SelectItem value="1500">1500 ms</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+              </>
+            )}
+
+            {(config.hotkey.mode === 'vox' || config.hotkey.mode === 'vad') && (
+              <div>
+                <p className="mb-1 text-sm">快捷键覆盖</p>
+                <Select
+                  value={config.hotkey.vox_override}
+                  onValueChange={(value) =>
+                    setConfig((previous) => ({
+                      ...previous,
+                      hotkey: { ...previous.hotkey, vox_override: value as VoxOverride },
+                    }))
+                  }
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="mute">按住强制静音</SelectItem>
+                    <SelectItem value="talk">按住强制开麦</SelectItem>
+                    <SelectItem value="off">不覆盖</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+            )}
+
+            <div>
+              <p className="mb-1 text-sm">松键挂起</p>
+              <Select
+                value={String(config.hotkey.hang_time_ms)}
+                onValueChange={(value) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    hotkey: { ...previous.hotkey, hang_time_ms: Number(value) },
+                  }))
+                }
+              >
+                <SelectTrigger>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="0">关闭</SelectItem>
+                  <SelectItem value="150">150 ms</SelectItem>
+                  <SelectItem value="300">300 ms</SelectItem>
+                  <SelectItem value="500">500 ms</SelectItem>
+                  <SelectItem value="1000">1000 ms</SelectItem>
+                </SelectContent>
+              </Select>
+              <p className="mt-1 text-xs opacity-70">按住说话松键后继续保持开麦一段时间，避免吞掉句尾。</p>
+            </div>
+
+            <div>
+              <p className="mb-1 text-sm">开麦预录</p>
+              <Select
+                value={String(config.engine.preroll_ms)}
+                onValueChange={(value) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    engine: { ...previous.engine, preroll_ms: Number(value) },
+                  }))
+                }
+              >
+                <SelectTrigger>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="0">关闭</SelectItem>
+                  <SelectItem value="100">100 ms</SelectItem>
+                  <SelectItem value="150">150 ms（推荐）</SelectItem>
+                  <SelectItem value="200">200 ms</SelectItem>
+                  <SelectItem value="300">300 ms</SelectItem>
                 </SelectContent>
               </Select>
+              <p className="mt-1 text-xs opacity-70">开麦时补上按键前的一小段声音，避免吞掉第一个字。</p>
             </div>
 
+            <div className="flex items-center justify-between">
+              <span className="text-sm">闭麦说话提醒</span>
+              <Switch
+                checked={config.engine.muted_speech.enabled}
+                onCheckedChange={(checked) =>
+                  setConfig((previous) => ({
+                    ...previous,
+                    engine: {
+                      ...previous.engine,
+                      muted_speech: { ...previous.engine.muted_speech, enabled: checked },
+                    },
+                  }))
+                }
+              />
+            </div>
+
+            {config.engine.muted_speech.enabled && (
+              <div className="grid grid-cols-2 gap-2">
+                <div>
+                  <p className="mb-1 text-sm">提醒灵敏度</p>
+                  <Select
+                    value={String(config.engine.muted_speech.threshold_db)}
+                    onValueChange={(value) =>
+                      setConfig((previous) => ({
+                        ...previous,
+                        engine: {
+                          ...previous.engine,
+                          muted_speech: { ...previous.engine.muted_speech, threshold_db: Number(value) },
+                        },
+                      }))
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="-50">高</SelectItem>
+                      <SelectItem value="-42">中（推荐）</SelectItem>
+                      <SelectItem value="-35">低</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+                <div>
+                  <p className="mb-1 text-sm">提醒间隔</p>
+                  <Select
+                    value={String(config.engine.muted_speech.cooldown_secs)}
+                    onValueChange={(value) =>
+                      setConfig((previous) => ({
+                        ...previous,
+                        engine: {
+                          ...previous.engine,
+                          muted_speech: { ...previous.engine.muted_speech, cooldown_secs: Number(value) },
+                        },
+                      }))
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      <SelectItem value="15">15 秒</SelectItem>
+                      <SelectItem value="30">30 秒（推荐）</SelectItem>
+                      <SelectItem value="60">60 秒</SelectItem>
+                      <SelectItem value="120">120 秒</SelectItem>
+                    </SelectContent>
+                  </Select>
+                </div>
+              </div>
+            )}
+
             <div className="flex items-center justify-between">
               <span className="text-sm">开机启动</span>
               <Switch
@@ -362,15 +1065,382 @@ export default function App() {
           </CardContent>
         </Card>
 
+        <Card>
+          <CardHeader>
+            <CardTitle>输入音色</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-3">
+            <p className="text-xs opacity-70">按当前物理麦克风分别保存，切换设备后自动套用对应设置。</p>
+            <div className="grid grid-cols-2 gap-2">
+              <div>
+                <p className="mb-1 text-sm">输入增益</p>
+                <Select
+                  value={String(inputProfile.input_gain_db)}
+                  disabled={!profileDeviceId}
+                  onValueChange={(value) =>
+                    updateInputProfile((profile) => ({ ...profile, input_gain_db: Number(value) }))
+                  }
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {[-12, -6, -3, 0, 3, 6, 12, 18].map((db) => (
+                      <SelectItem key={db} value={String(db)}>
+                        {db > 0 ? `+${db}` : db} dB
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+              <div>
+                <p className="mb-1 text-sm">高通滤波</p>
+                <Select
+                  value={String(inputProfile.high_pass_hz)}
+                  disabled={!profileDeviceId}
+                  onValueChange={(value) =>
+                    updateInputProfile((profile) => ({ ...profile, high_pass_hz: Number(value) }))
+                  }
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="0">关闭</SelectItem>
+                    {[60, 80, 100, 120, 150].map((hz) => (
+                      <SelectItem key={hz} value={String(hz)}>
+                        {hz} Hz
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+            </div>
+
+            {inputProfile.eq.map((band, index) => (
+              <div key={index} className="grid grid-cols-4 items-center gap-2">
+                <Select value={band.kind} onValueChange={(value) => updateEqBand(index, { kind: value as EqBandKind })}>
+                  <SelectTrigger>
+                    <SelectValue>{eqBandLabel(band.kind)}</SelectValue>
+                  </SelectTrigger>
+                  <SelectContent>
+                    {(['peaking', 'low_shelf', 'high_shelf'] as EqBandKind[]).map((kind) => (
+                      <SelectItem key={kind} value={kind}>
+                        {eqBandLabel(kind)}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <Select
+                  value={String(band.frequency_hz)}
+                  onValueChange={(value) => updateEqBand(index, { frequency_hz: Number(value) })}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {[100, 200, 400, 800, 1000, 2000, 3000, 5000, 8000].map((hz) => (
+                      <SelectItem key={hz} value={String(hz)}>
+                        {hz >= 1000 ? `${hz / 1000} kHz` : `${hz} Hz`}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <Select
+                  value={String(band.gain_db)}
+                  onValueChange={(value) => updateEqBand(index, { gain_db: Number(value) })}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {[-9, -6, -3, 0, 3, 6, 9].map((db) => (
+                      <SelectItem key={db} value={String(db)}>
+                        {db > 0 ? `+${db}` : db} dB
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <Button
+                  variant="ghost"
+                  size="sm"
+                  onClick={() =>
+                    updateInputProfile((profile) => ({ ...profile, eq: profile.eq.filter((_, i) => i !== index) }))
+                  }
+                >
+                  移除
+                </Button>
+              </div>
+            ))}
+
+            <Button
+              variant="outline"
+              size="sm"
+              disabled={!profileDeviceId || inputProfile.eq.length >= MAX_EQ_BANDS}
+              onClick={() =>
+                updateInputProfile((profile) => ({
+                  ...profile,
+                  eq: [...profile.eq, { kind: 'peaking', frequency_hz: 1000, gain_db: 0, q: 1 }],
+                }))
+              }
+            >
+              添加均衡频段
+            </Button>
+          </CardContent>
+        </Card>
+
+        <Card>
+          <CardHeader>
+            <CardTitle>音频处理</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-3">
+            {config.dsp.processors.length === 0 && (
+              <p className="text-sm opacity-70">未启用任何处理，麦克风信号直接进入门控。</p>
+            )}
+
+            {config.dsp.processors.map((processor, index) => (
+              <div key={processor.id} className="space-y-2 rounded-lg border border-border p-3">
+                <div className="flex items-center justify-between gap-2">
+                  <span className="text-sm">
+                    {index + 1}. {processorLabel(processor.kind)}
+                  </span>
+                  <div className="flex items-center gap-1">
+                    <Switch
+                      checked={!processor.bypass}
+                      onCheckedChange={(checked) =>
+                        updateProcessors((processors) =>
+                          processors.map((item) => (item.id === processor.id ? { ...item, bypass: !checked } : item)),
+                        )
+                      }
+                    />
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      disabled={index === 0}
+                      onClick={() => updateProcessors((processors) => moveItem(processors, index, -1))}
+                    >
+                      上移
+                    </Button>
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      disabled={index === config.dsp.processors.length - 1}
+                      onClick={() => updateProcessors((processors) => moveItem(processors, index, 1))}
+                    >
+                      下移
+                    </Button>
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      onClick={() =>
+                        updateProcessors((processors) => processors.filter((item) => item.id !== processor.id))
+                      }
+                    >
+                      移除
+                    </Button>
+                  </div>
+                </div>
+
+                {processor.kind === 'gain' && (
+                  <Select
+                    value={String(processor.gain_db)}
+                    onValueChange={(value) =>
+                      updateProcessors((processors) =>
+                        processors.map((item) =>
+                          item.id === processor.id && item.kind === 'gain' ? { ...item, gain_db: Number(value) } : item,
+                        ),
+                      )
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {[-12, -6, -3, 0, 3, 6, 12].map((db) => (
+                        <SelectItem key={db} value={String(db)}>
+                          {db > 0 ? `+${db}` : db} dB
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                )}
+
+                {processor.kind === 'noise_suppression' && (
+                  <Select
+                    value={String(processor.strength)}
+                    onValueChange={(value) =>
+                      updateProcessors((processors) =>
+                        processors.map((item) =>
+                          item.id === processor.id && item.kind === 'noise_suppression'
+                            ? { ...item, strength: Number(value) }
+                            : item,
+                        ),
+                      )
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {[
+                        { strength: 0.3, label: '弱' },
+                        { strength: 0.5, label: '中' },
+                        { strength: 0.7, label: '较强' },
+                        { strength: 1, label: '强' },
+                      ].map(({ strength, label }) => (
+                        <SelectItem key={strength} value={String(strength)}>
+                          {label}
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                )}
+
+                {processor.kind === 'agc' && (
+                  <Select
+                    value={String(processor.target_db)}
+                    onValueChange={(value) =>
+                      updateProcessors((processors) =>
+                        processors.map((item) =>
+                          item.id === processor.id && item.kind === 'agc' ? { ...item, target_db: Number(value) } : item,
+                        ),
+                      )
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {[-24, -21, -18, -15, -12].map((db) => (
+                        <SelectItem key={db} value={String(db)}>
+                          目标 {db} dBFS
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                )}
+
+                {processor.kind === 'compressor' && (
+                  <div className="grid grid-cols-2 gap-2">
+                    <Select
+                      value={String(processor.threshold_db)}
+                      onValueChange={(value) =>
+                        updateProcessors((processors) =>
+                          processors.map((item) =>
+                            item.id === processor.id && item.kind === 'compressor'
+                              ? { ...item, threshold_db: Number(value) }
+                              : item,
+                          ),
+                        )
+                      }
+                    >
+                      <SelectTrigger>
+                        <SelectValue />
+                      </SelectTrigger>
+                      <SelectContent>
+                        {[-36, -30, -24, -18, -12].map((db) => (
+                          <SelectItem key={db} value={String(db)}>
+                            门限 {db} dB
+                          </SelectItem>
+                        ))}
+                      </SelectContent>
+                    </Select>
+                    <Select
+                      value={String(processor.ratio)}
+                      onValueChange={(value) =>
+                        updateProcessors((processors) =>
+                          processors.map((item) =>
+                            item.id === processor.id && item.kind === 'compressor'
+                              ? { ...item, ratio: Number(value) }
+                              : item,
+                          ),
+                        )
+                      }
+                    >
+                      <SelectTrigger>
+                        <SelectValue />
+                      </SelectTrigger>
+                      <SelectContent>
+                        {[2, 3, 4, 6, 10].map((ratio) => (
+                          <SelectItem key={ratio} value={String(ratio)}>
+                            压缩比 {ratio}:1
+                          </SelectItem>
+                        ))}
+                      </SelectContent>
+                    </Select>
+                  </div>
+                )}
+
+                {processor.kind === 'limiter' && (
+                  <Select
+                    value={String(processor.ceiling_db)}
+                    onValueChange={(value) =>
+                      updateProcessors((processors) =>
+                        processors.map((item) =>
+                          item.id === processor.id && item.kind === 'limiter'
+                            ? { ...item, ceiling_db: Number(value) }
+                            : item,
+                        ),
+                      )
+                    }
+                  >
+                    <SelectTrigger>
+                      <SelectValue />
+                    </SelectTrigger>
+                    <SelectContent>
+                      {[-6, -3, -1, -0.3].map((db) => (
+                        <SelectItem key={db} value={String(db)}>
+                          上限 {db} dBFS
+                        </SelectItem>
+                      ))}
+                    </SelectContent>
+                  </Select>
+                )}
+              </div>
+            ))}
+
+            <div className="flex flex-wrap gap-2">
+              <Button variant="outline" size="sm" onClick={() => addProcessor('gain')}>
+                添加增益
+              </Button>
+              <Button variant="outline" size="sm" onClick={() => addProcessor('noise_suppression')}>
+                添加降噪
+              </Button>
+              <Button variant="outline" size="sm" onClick={() => addProcessor('agc')}>
+                添加自动增益
+              </Button>
+              <Button variant="outline" size="sm" onClick={() => addProcessor('compressor')}>
+                添加压缩器
+              </Button>
+              <Button variant="outline" size="sm" onClick={() => addProcessor('limiter')}>
+                添加限幅器
+              </Button>
+            </div>
+          </CardContent>
+        </Card>
+
         <Card>
           <CardHeader>
             <CardTitle>运行诊断</CardTitle>
           </CardHeader>
           <CardContent className="space-y-1 text-sm">
             <p>按键模式：{status ? modeLabel(status.gate_state.mode) : '-'}</p>
+            <p>松键挂起：{pendingCloseLabel(status?.gate_state)}</p>
+            <p>语音概率：{Math.round((status?.speech_probability ?? 0) * 100)}%</p>
             <p>链路状态：{engineLabel(status?.engine_state)}</p>
-            <p>缓冲水位：{status?.buffer_level_ms ?? 0} ms</p>
-            <p>XRuns：{status?.xruns ?? 0}</p>
+            <p>
+              缓冲水位：{status?.buffer_level_ms ?? 0} ms（目标 {status?.target_latency_ms ?? 0} ms，漂移修正{' '}
+              {status?.drift_ppm ?? 0} ppm）
+            </p>
+            <p>
+              采样率：{status?.input_sample_rate ?? 0} Hz → {status?.output_sample_rate ?? 0} Hz（比率{' '}
+              {(status?.resample_ratio ?? 1).toFixed(4)}）
+            </p>
+            <p>处理时延：{(status?.dsp_latency_ms ?? 0).toFixed(1)} ms</p>
+            <p>增益衰减：{(status?.gain_reduction_db ?? 0).toFixed(1)} dB</p>
+            <p>
+              XRuns：{status?.xruns ?? 0}（溢出 {status?.overflows ?? 0} / 欠载 {status?.underflows ?? 0}）
+            </p>
             <p>虚拟麦后端：{virtualMic ? `${virtualMic.backend}（${virtualMic.ready ? '就绪' : '未就绪'}）` : '-'}</p>
             <p>后端详情：{virtualMic?.detail ?? '-'}</p>
             <p>最近错误：{status?.last_error ?? '无'}</p>
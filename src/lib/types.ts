@@ -1,11 +1,28 @@
 export type DeviceDirection = 'input' | 'output';
 
+export interface ConfigSignature {
+  channels: number;
+  min_sample_rate: number;
+  max_sample_rate: number;
+  sample_format: string;
+}
+
+export interface DeviceFingerprint {
+  direction: DeviceDirection;
+  name: string;
+  host: string;
+  endpoint_id: string | null;
+  configs: ConfigSignature[];
+}
+
 export interface DeviceInfo {
   id: string;
   name: string;
   direction: DeviceDirection;
   is_default: boolean;
   is_virtual_candidate: boolean;
+  channels: number;
+  fingerprint: DeviceFingerprint;
 }
 
 export interface DeviceList {
@@ -13,16 +30,53 @@ export interface DeviceList {
   outputs: DeviceInfo[];
 }
 
-export type GateMode = 'ptt' | 'toggle' | 'hybrid';
+export interface DevicesChanged {
+  added: DeviceInfo[];
+  removed: DeviceInfo[];
+  devices: DeviceList;
+}
+
+export type GateMode = 'ptt' | 'toggle' | 'hybrid' | 'push_to_mute' | 'vox' | 'vad';
+
+export type VoxOverride = 'off' | 'mute' | 'talk';
 
 export interface HotkeyConfig {
   accelerator: string;
   mode: GateMode;
+  hang_time_ms: number;
+  tap_threshold_ms: number;
+  vox_override: VoxOverride;
 }
 
+export type InputChannel =
+  | { kind: 'average' }
+  | { kind: 'left' }
+  | { kind: 'right' }
+  | { kind: 'channel'; index: number }
+  | { kind: 'auto' };
+
+export type InputRouteMode = 'fixed' | 'follow_default';
+
 export interface AudioRouteConfig {
+  input_mode: InputRouteMode;
   input_device_id: string;
   bridge_output_device_id: string;
+  reference_output_device_id: string;
+  input_channel: InputChannel;
+  passthrough: boolean;
+  output_channels: number[];
+  input_fallback_ids: string[];
+  output_fallback_ids: string[];
+}
+
+export type RouteChangeReason = 'device_lost' | 'preferred_restored' | 'default_changed';
+
+export interface RouteChange {
+  direction: DeviceDirection;
+  from_device_id: string;
+  to_device_id: string;
+  to_device_name: string;
+  reason: RouteChangeReason;
 }
 
 export interface GateState {
@@ -30,6 +84,7 @@ export interface GateState {
   mode: GateMode;
   last_source: string;
   changed_at: string;
+  pending_close_at?: string | null;
 }
 
 export type EngineState = 'idle' | 'running' | 'error';
@@ -38,19 +93,128 @@ export interface RuntimeStatus {
   engine_state: EngineState;
   buffer_level_ms: number;
   xruns: number;
+  overflows: number;
+  underflows: number;
+  target_latency_ms: number;
+  drift_ppm: number;
+  input_sample_rate: number;
+  output_sample_rate: number;
+  resample_ratio: number;
+  dsp_latency_ms: number;
+  speech_probability: number;
+  gain_reduction_db: number;
+  active_input_device_id: string;
+  active_output_device_id: string;
   last_error: string | null;
   gate_state: GateState;
 }
 
+export interface LevelReading {
+  peak_db: number;
+  rms_db: number;
+}
+
+export interface AudioLevels {
+  pre_gate: LevelReading;
+  post_gate: LevelReading;
+}
+
+export interface MutedSpeechWarning {
+  level: LevelReading;
+  gate_state: GateState;
+}
+
 export interface VirtualMicStatus {
   backend: string;
   ready: boolean;
   detail: string;
 }
 
+export type EnvelopeCurve = 'linear' | 'equal_power' | 'exponential';
+
+export interface EnvelopeConfig {
+  attack_ms: number;
+  release_ms: number;
+  curve: EnvelopeCurve;
+}
+
+export interface VoxConfig {
+  open_threshold_db: number;
+  close_threshold_db: number;
+  attack_ms: number;
+  hold_ms: number;
+  min_open_ms: number;
+}
+
+export interface VadConfig {
+  open_probability: number;
+  close_probability: number;
+  hold_ms: number;
+  min_open_ms: number;
+}
+
+export interface MutedSpeechConfig {
+  enabled: boolean;
+  threshold_db: number;
+  min_speech_ms: number;
+  cooldown_secs: number;
+}
+
+export interface EngineConfig {
+  target_latency_ms: number;
+  envelope: EnvelopeConfig;
+  preroll_ms: number;
+  vox: VoxConfig;
+  vad: VadConfig;
+  muted_speech: MutedSpeechConfig;
+}
+
+export type ProcessorSettings =
+  | { kind: 'gain'; gain_db: number }
+  | { kind: 'noise_suppression'; strength: number }
+  | { kind: 'agc'; target_db: number; max_gain_db: number }
+  | {
+      kind: 'compressor';
+      threshold_db: number;
+      ratio: number;
+      knee_db: number;
+      attack_ms: number;
+      release_ms: number;
+      makeup_db: number;
+    }
+  | { kind: 'limiter'; ceiling_db: number; lookahead_ms: number; release_ms: number };
+
+export type ProcessorConfig = ProcessorSettings & {
+  id: number;
+  bypass: boolean;
+};
+
+export interface DspChainConfig {
+  processors: ProcessorConfig[];
+}
+
+export type EqBandKind = 'peaking' | 'low_shelf' | 'high_shelf';
+
+export interface EqBand {
+  kind: EqBandKind;
+  frequency_hz: number;
+  gain_db: number;
+  q: number;
+}
+
+export interface VoiceShapingConfig {
+  input_gain_db: number;
+  high_pass_hz: number;
+  eq: EqBand[];
+}
+
 export interface AppConfig {
   route: AudioRouteConfig;
   hotkey: HotkeyConfig;
+  engine: EngineConfig;
+  dsp: DspChainConfig;
+  input_profiles: Record<string, VoiceShapingConfig>;
+  device_fingerprints: Record<string, DeviceFingerprint>;
   launch_on_startup: boolean;
   minimize_to_tray: boolean;
 }